@@ -3,7 +3,6 @@ use std::env;
 use std::path::PathBuf;
 // use std::process::Command;
 // use pkg_config;
-use metadeps;
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=wrapper.h");
@@ -33,6 +32,9 @@ fn main() {
         /* su home init / deinit */
         .allowlist_function("su_home_init")
         .allowlist_function("su_home_deinit")
+        .allowlist_function("su_home_new")
+        .allowlist_function("su_home_unref")
+        .allowlist_function("su_free")
         // .allowlist_type("su_home_t")
         .opaque_type("su_home_t")
         /* su root class */
@@ -44,6 +46,11 @@ fn main() {
         .allowlist_function("su_root_sleep")
         .allowlist_function("su_root_threading")
         .opaque_type("su_root_t")
+        /* su timer class */
+        .allowlist_function("su_root_task")
+        .allowlist_function("su_timer_create")
+        .allowlist_function("su_timer_run")
+        .allowlist_function("su_timer_destroy")
         /* nua class */
         .allowlist_function("nua_create")
         .allowlist_function("nua_destroy")
@@ -54,9 +61,35 @@ fn main() {
         .allowlist_function("nua_handle_destroy")
         .allowlist_function("nua_handle_has_active_call")
         .allowlist_function("nua_handle_has_call_on_hold")
+        .allowlist_function("nua_handle_ref")
         .allowlist_function("nua_handle_unref")
+        .allowlist_function("nua_handle_bind")
+        .allowlist_function("nua_handle_magic")
+        .allowlist_function("nua_handle_make_replaces")
+        .allowlist_function("nua_handle_by_replaces")
         .allowlist_function("nua_message") /* no namespaced, but it needs a nua_handle */
         .allowlist_function("nua_invite") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_respond") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_bye") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_cancel") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_ack") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_register") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_unregister") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_subscribe") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_unsubscribe") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_notifier") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_authorize") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_notify") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_terminate") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_publish") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_unpublish") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_refer") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_info") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_prack") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_update") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_options") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_method") /* no namespaced, but it needs a nua_handle */
+        .allowlist_function("nua_authenticate") /* no namespaced, but it needs a nua_handle */
         // .allowlist_type("nua_t")
         // .allowlist_type("nua_s")
         /* tags: su_tag.h */
@@ -75,6 +108,19 @@ fn main() {
         .allowlist_var("siptag_content_type_str")
         .allowlist_var("siptag_payload_str")
         .allowlist_var("siptag_to_str")
+        .allowlist_var("siptag_contact_str")
+        .allowlist_var("siptag_expires_str")
+        .allowlist_var("siptag_event_str")
+        .allowlist_var("siptag_accept_str")
+        .allowlist_var("siptag_etag_str")
+        .allowlist_var("siptag_if_match_str")
+        .allowlist_var("siptag_refer_to_str")
+        .allowlist_var("siptag_replaces_str")
+        .allowlist_var("siptag_supported_str")
+        .allowlist_var("siptag_require_str")
+        .allowlist_var("siptag_www_authenticate_str")
+        .allowlist_var("siptag_proxy_authenticate_str")
+        .allowlist_var("siptag_header_str")
         /* tags: soa_tag.h */
         .allowlist_var("soatag_user_sdp_str")
         /* tags: nua_tag.h */
@@ -82,8 +128,64 @@ fn main() {
         .allowlist_var("nutag_m_username")
         .allowlist_var("nutag_m_display")
         .allowlist_var("nutag_callstate")
+        .allowlist_var("nutag_offer_sent")
+        .allowlist_var("nutag_answer_sent")
+        .allowlist_var("nutag_offer_recv")
+        .allowlist_var("nutag_answer_recv")
+        .allowlist_var("nutag_registrar")
+        .allowlist_var("nutag_outbound")
+        .allowlist_var("nutag_m_params")
+        .allowlist_var("nutag_m_features")
+        .allowlist_var("nutag_allow")
+        .allowlist_var("nutag_allow_events")
+        .allowlist_var("nutag_method")
+        .allowlist_var("nutag_appl_method")
+        .allowlist_var("nutag_auth")
+        .allowlist_var("nutag_substate")
+        .allowlist_var("nutag_refer_event")
+        .allowlist_var("nutag_notify_refer")
+        .allowlist_var("nutag_early_media")
+        .allowlist_var("nutag_autoprack")
+        .allowlist_var("nutag_session_timer")
+        .allowlist_var("nutag_min_se")
+        .allowlist_var("nutag_session_refresher")
+        .allowlist_type("nua_session_refresher")
+        .allowlist_type("nua_callstate")
+        .allowlist_var("nutag_update_refresh")
+        .allowlist_type("nua_substate")
+        /* tags: nea.h */
+        .allowlist_var("neatag_sub")
         /* sip.h */
         .allowlist_type("sip_t")
+        .allowlist_var("sip_replaces_class")
+        .allowlist_var("sip_event_class")
+        .allowlist_function("sip_default_mclass")
+        .allowlist_function("sip_complete_message")
+        .allowlist_function("sip_payload_create")
+        .allowlist_var("sip_request_class")
+        .allowlist_var("sip_status_class")
+        .allowlist_var("sip_unknown_class")
+        .allowlist_var("sip_error_class")
+        /* msg.h */
+        .allowlist_function("msg_create")
+        .allowlist_function("msg_destroy")
+        .allowlist_function("msg_object")
+        .allowlist_function("msg_extract")
+        .allowlist_function("msg_buf_alloc")
+        .allowlist_function("msg_buf_commit")
+        .allowlist_function("msg_buf_committed")
+        .allowlist_function("msg_serialize")
+        .allowlist_function("msg_as_string")
+        /* msg_mclass.h */
+        .allowlist_function("msg_find_hclass")
+        .allowlist_function("msg_hclass_offset")
+        /* msg_header.h */
+        .allowlist_function("msg_header_make")
+        .allowlist_function("msg_header_as_string")
+        .allowlist_function("msg_header_add_str")
+        .allowlist_function("msg_header_insert")
+        .allowlist_function("msg_header_remove")
+        .allowlist_function("msg_header_remove_all")
         /* url.h */
         .allowlist_function("url_e")
         /* msg_types.h */
@@ -104,7 +206,7 @@ fn main() {
         // .allowlist_type("nua_t")
         .clang_args(
             sofia_sip_ua_include_paths
-                .into_iter()
+                .iter()
                 .map(|i| format!("-I{}", i.to_str().unwrap())),
         )
         // Tell cargo to invalidate the built crate whenever any of the
@@ -0,0 +1,270 @@
+//! Integration with the tokio runtime (feature `async`).
+//!
+//! The futures of this module run the reactor of sofia-sip without blocking
+//! (`su_root_step` with a zero timeout) whenever they are polled and sleep on
+//! a tokio timer until the next sofia-sip timer expires or, at most,
+//! [`POLL_INTERVAL`]: an incoming packet may wait that long to be read.
+//!
+//! This is polling, the descriptors of the reactor are not registered with
+//! tokio (`AsyncFd`): sofia-sip has no public API exporting them. They are
+//! kept by the port of the root (its epoll or poll set), whose interface
+//! (`su_port.h`) is not installed. Only the glib port
+//! (`libsofia-sip-ua-glib`) exposes them, through the `GMainContext` the
+//! root is attached to, and using it would add glib to the dependencies.
+//!
+//! An idle reactor still wakes up every [`POLL_INTERVAL`], use
+//! [`drive_with_interval`] to trade the latency of incoming packets for
+//! fewer wake ups.
+//!
+//! Nothing here is `Send`: use a current thread runtime or a
+//! `tokio::task::LocalSet`.
+
+use crate::nua::event::RawEvent;
+use crate::nua::events::Events;
+use crate::nua::handle::ReplySlot;
+use crate::nua::Event;
+use crate::nua::Handle;
+use crate::nua::Nua;
+use crate::su::Root;
+use crate::sys;
+use crate::tag::Tag;
+
+use futures_core::Stream;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio::time::Sleep;
+
+/// Longest time between two steps of the reactor, the sockets of the stack
+/// are only read when it runs.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/* run the reactor of sofia-sip from the poll of a future */
+#[derive(Debug)]
+struct Driver {
+    root: *mut sys::su_root_t,
+    /* longest time between two steps */
+    interval: Duration,
+    /* created on first poll, a tokio runtime is needed */
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Driver {
+    fn new(root: *mut sys::su_root_t) -> Self {
+        Self::with_interval(root, POLL_INTERVAL)
+    }
+
+    fn with_interval(root: *mut sys::su_root_t, interval: Duration) -> Self {
+        Driver {
+            root,
+            interval,
+            sleep: None,
+        }
+    }
+
+    /* run the reactor once without blocking and schedule the next wake up */
+    fn poll_step(&mut self, cx: &mut Context<'_>) {
+        /* milliseconds to the next timer, negative if there is none */
+        let next = Root::_step(self.root, 0);
+        let wait = if next < 0 {
+            self.interval
+        } else {
+            Duration::from_millis(next as u64).min(self.interval)
+        };
+        let deadline = Instant::now() + wait;
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline)));
+        sleep.as_mut().reset(deadline);
+        if sleep.as_mut().poll(cx).is_ready() {
+            cx.waker().wake_by_ref();
+        }
+    }
+}
+
+/// Future running the reactor forever, to be spawned on a `LocalSet` when
+/// the events are delivered to closures (see [`drive`]).
+#[derive(Debug)]
+pub struct Drive<'a> {
+    _root: &'a Root,
+    driver: Driver,
+}
+
+impl<'a> Future for Drive<'a> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.driver.poll_step(cx);
+        Poll::Pending
+    }
+}
+
+/// Run `root` inside the tokio runtime instead of [`Root::run`].
+pub fn drive(root: &Root) -> Drive<'_> {
+    drive_with_interval(root, POLL_INTERVAL)
+}
+
+/// Same as [`drive`], stepping the reactor at least every `interval`
+/// instead of [`POLL_INTERVAL`].
+pub fn drive_with_interval(root: &Root, interval: Duration) -> Drive<'_> {
+    Drive {
+        _root: root,
+        driver: Driver::with_interval(root.c_ptr, interval),
+    }
+}
+
+/// Stream of the events of a NUA stack, see [`Nua::event_stream`].
+///
+/// The stream ends when the NUA stack is destroyed.
+#[derive(Debug)]
+pub struct EventStream {
+    events: Events,
+    driver: Driver,
+}
+
+impl Stream for EventStream {
+    type Item = RawEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<RawEvent>> {
+        if let Some(event) = self.events.pop() {
+            return Poll::Ready(Some(event));
+        }
+        if !self.events.nua_alive.get() {
+            return Poll::Ready(None);
+        }
+        self.driver.poll_step(cx);
+        match self.events.pop() {
+            Some(event) => Poll::Ready(Some(event)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Future of the final response of a request sent on a handle.
+///
+/// It resolves to `None` if the handle or the NUA stack is destroyed before
+/// the response.
+#[derive(Debug)]
+pub struct ReplyFuture {
+    handle: Handle,
+    slot: ReplySlot,
+    driver: Driver,
+}
+
+impl ReplyFuture {
+    fn take(&self) -> Option<RawEvent> {
+        self.slot.borrow_mut().take()
+    }
+}
+
+impl Future for ReplyFuture {
+    type Output = Option<RawEvent>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<RawEvent>> {
+        if let Some(reply) = self.take() {
+            return Poll::Ready(Some(reply));
+        }
+        if self.handle.c_ptr().is_null() {
+            return Poll::Ready(None);
+        }
+        self.driver.poll_step(cx);
+        match self.take() {
+            Some(reply) => Poll::Ready(Some(reply)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Future of the shutdown of a NUA stack, see [`Nua::shutdown_async`].
+#[derive(Debug)]
+pub struct Shutdown<'n, 'a> {
+    nua: &'n Nua<'a>,
+    driver: Driver,
+}
+
+impl<'n, 'a> Future for Shutdown<'n, 'a> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.nua.shutdown_completed() {
+            return Poll::Ready(());
+        }
+        self.driver.poll_step(cx);
+        if self.nua.shutdown_completed() {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl<'a> Nua<'a> {
+    ///Receive the events through a [`Stream`] instead of a callback,
+    ///see [`Nua::events`].
+    pub fn event_stream(&mut self) -> EventStream {
+        let events = self.events();
+        let driver = Driver::new(events.root);
+        EventStream { events, driver }
+    }
+
+    ///Shutdown NUA stack without blocking the runtime.
+    pub fn shutdown_async(&self) -> Shutdown<'_, 'a> {
+        self.shutdown();
+        Shutdown {
+            nua: self,
+            driver: Driver::new(self.root().c_ptr),
+        }
+    }
+}
+
+impl Handle {
+    /// Future of the next final response reported by `event` (eg:
+    /// `ReplyInvite`), call it before sending the request.
+    ///
+    /// A challenge answered by the credential provider of the stack does
+    /// not resolve it.
+    pub fn reply(&self, event: Event) -> ReplyFuture {
+        ReplyFuture {
+            handle: self.clone(),
+            slot: self._wait_reply(event),
+            driver: Driver::new(self.inner.root),
+        }
+    }
+
+    /// Send an INVITE, the future resolves with its final response.
+    pub fn invite_async(&self, tags: &[Tag]) -> ReplyFuture {
+        let reply = self.reply(Event::ReplyInvite);
+        self.invite(tags);
+        reply
+    }
+
+    /// Send a BYE, the future resolves with its final response.
+    pub fn bye_async(&self, tags: &[Tag]) -> ReplyFuture {
+        let reply = self.reply(Event::ReplyBye);
+        self.bye(tags);
+        reply
+    }
+
+    /// Send a MESSAGE, the future resolves with its final response.
+    pub fn message_async(&self, tags: &[Tag]) -> ReplyFuture {
+        let reply = self.reply(Event::ReplyMessage);
+        self.message(tags);
+        reply
+    }
+
+    /// Send a REGISTER, the future resolves with its final response.
+    pub fn register_async(&self, tags: &[Tag]) -> ReplyFuture {
+        let reply = self.reply(Event::ReplyRegister);
+        self.register(tags);
+        reply
+    }
+
+    /// Send an OPTIONS, the future resolves with its final response.
+    pub fn options_async(&self, tags: &[Tag]) -> ReplyFuture {
+        let reply = self.reply(Event::ReplyOptions);
+        self.options(tags);
+        reply
+    }
+}
@@ -0,0 +1,159 @@
+use crate::error::Error;
+use crate::result::Result;
+
+use std::fmt;
+
+/// Content type of DTMF relay bodies (`Signal=` and `Duration=` lines).
+pub const DTMF_RELAY_CONTENT_TYPE: &str = "application/dtmf-relay";
+/// Content type of DTMF bodies (a single digit).
+pub const DTMF_CONTENT_TYPE: &str = "application/dtmf";
+
+/// Default duration of a DTMF event, in milliseconds.
+pub const DTMF_DEFAULT_DURATION: u32 = 250;
+
+/// A DTMF event carried by a SIP INFO request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dtmf {
+    signal: char,
+    duration: u32,
+}
+
+impl Dtmf {
+    /// Create a DTMF event, `signal` is one of `0-9`, `*`, `#`, `A-D`
+    /// and `duration` is in milliseconds.
+    pub fn new(signal: char, duration: u32) -> Result<Self> {
+        let signal = signal.to_ascii_uppercase();
+        if !Self::is_valid_signal(signal) {
+            return Err(Error::ParseDtmfError);
+        }
+        Ok(Self { signal, duration })
+    }
+
+    fn is_valid_signal(signal: char) -> bool {
+        matches!(signal, '0'..='9' | '*' | '#' | 'A'..='D')
+    }
+
+    pub fn signal(&self) -> char {
+        self.signal
+    }
+
+    /// Duration in milliseconds.
+    pub fn duration(&self) -> u32 {
+        self.duration
+    }
+
+    /// Encode as an `application/dtmf-relay` body.
+    pub fn to_dtmf_relay(&self) -> String {
+        format!("Signal={}\r\nDuration={}\r\n", self.signal, self.duration)
+    }
+
+    /// Encode as an `application/dtmf` body (the duration is not sent).
+    pub fn to_dtmf(&self) -> String {
+        self.signal.to_string()
+    }
+
+    /// Decode an `application/dtmf-relay` body.
+    ///
+    /// `Duration` is optional, [`DTMF_DEFAULT_DURATION`] is used when missing.
+    pub fn from_dtmf_relay(body: &str) -> Result<Self> {
+        let mut signal = None;
+        let mut duration = DTMF_DEFAULT_DURATION;
+        for line in body.lines() {
+            let mut parts = line.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+            if name.eq_ignore_ascii_case("signal") {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => signal = Some(c),
+                    _ => return Err(Error::ParseDtmfError),
+                }
+            } else if name.eq_ignore_ascii_case("duration") {
+                duration = value.parse().map_err(|_| Error::ParseDtmfError)?;
+            }
+        }
+        Self::new(signal.ok_or(Error::ParseDtmfError)?, duration)
+    }
+
+    /// Decode an `application/dtmf` body.
+    pub fn from_dtmf(body: &str) -> Result<Self> {
+        let mut chars = body.trim().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Self::new(c, DTMF_DEFAULT_DURATION),
+            _ => Err(Error::ParseDtmfError),
+        }
+    }
+
+    /// Decode a body according to its content type.
+    pub fn from_body(content_type: &str, body: &str) -> Result<Self> {
+        if content_type.eq_ignore_ascii_case(DTMF_RELAY_CONTENT_TYPE) {
+            Self::from_dtmf_relay(body)
+        } else if content_type.eq_ignore_ascii_case(DTMF_CONTENT_TYPE) {
+            Self::from_dtmf(body)
+        } else {
+            Err(Error::ParseDtmfError)
+        }
+    }
+}
+
+impl fmt::Display for Dtmf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({} ms)", self.signal, self.duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_dtmf() {
+        let dtmf = Dtmf::new('5', 160).unwrap();
+        assert_eq!(dtmf.signal(), '5');
+        assert_eq!(dtmf.duration(), 160);
+
+        assert_eq!(Dtmf::new('a', 100).unwrap().signal(), 'A');
+        assert!(Dtmf::new('E', 100).is_err());
+        assert!(Dtmf::new('x', 100).is_err());
+    }
+
+    #[test]
+    fn test_encode_dtmf_relay() {
+        let dtmf = Dtmf::new('#', 160).unwrap();
+        assert_eq!(dtmf.to_dtmf_relay(), "Signal=#\r\nDuration=160\r\n");
+    }
+
+    #[test]
+    fn test_decode_dtmf_relay() {
+        let dtmf = Dtmf::from_dtmf_relay("Signal=1\r\nDuration=100\r\n").unwrap();
+        assert_eq!(dtmf, Dtmf::new('1', 100).unwrap());
+
+        let dtmf = Dtmf::from_dtmf_relay("signal= *\nduration = 80").unwrap();
+        assert_eq!(dtmf, Dtmf::new('*', 80).unwrap());
+
+        let dtmf = Dtmf::from_dtmf_relay("Signal=9\r\n").unwrap();
+        assert_eq!(dtmf.duration(), DTMF_DEFAULT_DURATION);
+
+        assert!(Dtmf::from_dtmf_relay("Duration=100\r\n").is_err());
+        assert!(Dtmf::from_dtmf_relay("Signal=12\r\n").is_err());
+        assert!(Dtmf::from_dtmf_relay("Signal=1\r\nDuration=abc\r\n").is_err());
+    }
+
+    #[test]
+    fn test_encode_and_decode_dtmf() {
+        let dtmf = Dtmf::new('7', DTMF_DEFAULT_DURATION).unwrap();
+        assert_eq!(dtmf.to_dtmf(), "7");
+        assert_eq!(Dtmf::from_dtmf("7\r\n").unwrap(), dtmf);
+        assert!(Dtmf::from_dtmf("").is_err());
+        assert!(Dtmf::from_dtmf("78").is_err());
+    }
+
+    #[test]
+    fn test_decode_dtmf_from_body() {
+        let dtmf = Dtmf::from_body("application/dtmf-relay", "Signal=3\r\nDuration=120\r\n");
+        assert_eq!(dtmf.unwrap(), Dtmf::new('3', 120).unwrap());
+        let dtmf = Dtmf::from_body("application/dtmf", "3");
+        assert_eq!(dtmf.unwrap().signal(), '3');
+        assert!(Dtmf::from_body("text/plain", "3").is_err());
+    }
+}
@@ -6,6 +6,15 @@ pub enum Error {
     UrlError,
     ConvertToCStringError,
     MissingTagConversion,
+    ParseDtmfError,
+    CreateTimerError,
+    CreateMessageError,
+    ParseMessageError,
+    IncompleteMessageError,
+    InvalidHeaderError,
+    SerializeMessageError,
+    HandleDestroyedError,
+    TimeoutError,
 }
 
 // https://chromium.googlesource.com/chromiumos/docs/+/master/constants/errnos.md
@@ -1,3 +1,6 @@
+#[cfg(feature = "async")]
+pub mod aio;
+pub mod dtmf;
 pub mod error;
 pub mod nua;
 pub mod result;
@@ -6,10 +9,12 @@ pub mod su;
 pub mod sys;
 pub mod tag;
 
+pub use crate::dtmf::Dtmf;
 pub use crate::nua::event::Event as NuaEvent;
 pub use crate::nua::Handle;
 pub use crate::nua::Nua;
 pub use crate::sip::Sip;
+pub use crate::sip::SipRef;
 pub use crate::su::Root;
 pub use crate::tag::builder::Builder as TagBuilder;
 pub use crate::tag::Tag;
@@ -0,0 +1,80 @@
+use crate::sip::SipAuth;
+
+/// Username and password used to answer a challenge (401/407).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credentials {
+    username: String,
+    password: String,
+}
+
+impl Credentials {
+    pub fn new(username: &str, password: &str) -> Self {
+        Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    pub fn username(&self) -> &String {
+        &self.username
+    }
+
+    pub fn password(&self) -> &String {
+        &self.password
+    }
+
+    /// Value of `NUTAG_AUTH()` answering `challenge`, eg:
+    /// `Digest:"example.com":alice:secret`.
+    ///
+    /// Returns `None` if the challenge has no realm.
+    pub fn nutag_auth(&self, challenge: &SipAuth) -> Option<String> {
+        let realm = challenge.realm()?;
+        Some(format!(
+            "{}:\"{}\":{}:{}",
+            challenge.scheme(),
+            realm,
+            self.username,
+            self.password
+        ))
+    }
+}
+
+/// Provide credentials for a realm, used by [`Nua`](crate::nua::Nua) to
+/// answer automatically the challenges received by its handles.
+///
+/// It is implemented for closures `Fn(&str) -> Option<Credentials>`.
+pub trait CredentialProvider {
+    /// Credentials for `realm`, `None` if the challenge must not be answered.
+    fn credentials(&self, realm: &str) -> Option<Credentials>;
+}
+
+impl<F: Fn(&str) -> Option<Credentials>> CredentialProvider for F {
+    fn credentials(&self, realm: &str) -> Option<Credentials> {
+        self(realm)
+    }
+}
+
+/// The same credentials for every realm.
+impl CredentialProvider for Credentials {
+    fn credentials(&self, _realm: &str) -> Option<Credentials> {
+        Some(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_provider_closure() {
+        let provider = |realm: &str| match realm {
+            "example.com" => Some(Credentials::new("alice", "secret")),
+            _ => None,
+        };
+        assert_eq!(
+            provider.credentials("example.com"),
+            Some(Credentials::new("alice", "secret"))
+        );
+        assert_eq!(provider.credentials("example.org"), None);
+    }
+}
@@ -3,7 +3,10 @@ use crate::nua::nua::Handle;
 use crate::nua::Nua;
 use crate::result::Result;
 use crate::sip::Sip;
+use crate::sip::SipContact;
+use crate::sip::SipFrag;
 use crate::sys;
+use crate::tag::CallState;
 use crate::tag::Tag;
 
 pub type EventClosure =
@@ -35,7 +38,7 @@ macro_rules! back_to_enum {
     }
 }
 back_to_enum! {
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, PartialEq)]
     pub enum Event {
         IncomingError = sys::nua_event_e_nua_i_error as isize,
         IncomingInvite = sys::nua_event_e_nua_i_invite as isize,
@@ -52,7 +55,7 @@ back_to_enum! {
         IncomingPublish = sys::nua_event_e_nua_i_publish as isize,
         IncomingPrack = sys::nua_event_e_nua_i_prack as isize,
         IncomingInfo = sys::nua_event_e_nua_i_info as isize,
-        Incomingupdate = sys::nua_event_e_nua_i_update as isize,
+        IncomingUpdate = sys::nua_event_e_nua_i_update as isize,
         IncomingMessage = sys::nua_event_e_nua_i_message as isize,
         IncomingChat = sys::nua_event_e_nua_i_chat as isize,
         IncomingSubscribe = sys::nua_event_e_nua_i_subscribe as isize,
@@ -94,3 +97,305 @@ back_to_enum! {
         IncomingRegister = sys::nua_event_e_nua_i_register as isize,
     }
 }
+
+/// Call state and offer/answer progress reported by an `IncomingState` event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CallStateInfo {
+    pub state: CallState,
+    pub offer_sent: bool,
+    pub answer_sent: bool,
+    pub offer_recv: bool,
+    pub answer_recv: bool,
+}
+
+impl CallStateInfo {
+    /// Decode the tags of an `IncomingState` event, `None` if there is no
+    /// `NuCallstate` tag.
+    ///
+    /// The offer/answer tags are only sent by the stack when set.
+    pub fn from_tags(tags: &[Tag]) -> Option<Self> {
+        let mut state = None;
+        let mut info = CallStateInfo {
+            state: CallState::Init,
+            offer_sent: false,
+            answer_sent: false,
+            offer_recv: false,
+            answer_recv: false,
+        };
+        for tag in tags {
+            match tag {
+                Tag::NuCallstate(v) => state = Some(*v),
+                Tag::NuOfferSent(v) => info.offer_sent = *v,
+                Tag::NuAnswerSent(v) => info.answer_sent = *v,
+                Tag::NuOfferRecv(v) => info.offer_recv = *v,
+                Tag::NuAnswerRecv(v) => info.answer_recv = *v,
+                _ => {}
+            }
+        }
+        info.state = state?;
+        Some(info)
+    }
+}
+
+/// Event as received from the stack, with the arguments of [`EventClosure`].
+#[derive(Debug, Clone)]
+pub struct RawEvent {
+    pub event: Event,
+    pub status: u32,
+    pub phrase: String,
+    pub handle: Option<Handle>,
+    pub sip: Sip,
+    pub tags: Vec<Tag>,
+}
+
+/// Typed payload of an event (see `Nua::event_callback`).
+///
+/// Events without a typed payload (or missing their handle) are delivered
+/// as [`EventData::Raw`].
+#[derive(Debug)]
+pub enum EventData {
+    IncomingInvite {
+        handle: Handle,
+        request: Sip,
+    },
+    IncomingCancel {
+        handle: Handle,
+    },
+    IncomingBye {
+        handle: Handle,
+        request: Sip,
+    },
+    IncomingMessage {
+        handle: Handle,
+        request: Sip,
+    },
+    IncomingRegister {
+        handle: Handle,
+        request: Sip,
+    },
+    IncomingState {
+        handle: Handle,
+        state: CallStateInfo,
+    },
+    IncomingActive {
+        handle: Handle,
+    },
+    IncomingTerminated {
+        handle: Handle,
+    },
+    ReplyInvite {
+        handle: Handle,
+        status: u32,
+        phrase: String,
+        response: Sip,
+    },
+    ReplyBye {
+        handle: Handle,
+        status: u32,
+        phrase: String,
+    },
+    ReplyMessage {
+        handle: Handle,
+        status: u32,
+        phrase: String,
+        response: Sip,
+    },
+    ReplyRegister {
+        handle: Handle,
+        status: u32,
+        phrase: String,
+        /// Expiry of the registration, in seconds: the `expires` parameter
+        /// of its Contact in the response, or else the Expires header.
+        expires: Option<u32>,
+        /// Bindings registered by the registrar.
+        contacts: Vec<SipContact>,
+    },
+    ReplyUnregister {
+        handle: Handle,
+        status: u32,
+        phrase: String,
+    },
+    ReplyShutdown {
+        status: u32,
+        phrase: String,
+    },
+    /// Progress of the call made by the target of a REFER, reported by
+    /// the NOTIFY (`Event: refer`) received on the handle that sent it.
+    ReferProgress {
+        handle: Handle,
+        /// Status of the referred call, final if >= 200.
+        status: u32,
+        phrase: String,
+    },
+    Raw(RawEvent),
+}
+
+/* status line of the sipfrag of a NOTIFY reporting the progress of a REFER */
+fn refer_progress(sip: &Sip) -> Option<SipFrag> {
+    if !sip.event().r#type().eq_ignore_ascii_case("refer") {
+        return None;
+    }
+    sip.sipfrag()
+}
+
+/* expiry of the registration done on `handle`: the `expires` parameter of
+its binding in the response, or else the Expires header */
+fn registration_expires(handle: &Handle, sip: &Sip) -> Option<u32> {
+    let contacts = sip.contacts();
+    let binding = match handle.inner.register_contact.borrow().as_deref() {
+        Some(url) => contacts.iter().find(|contact| contact.url() == url),
+        /* contact made by the stack, only known if it is the single binding */
+        None if contacts.len() == 1 => contacts.first(),
+        None => None,
+    };
+    binding
+        .and_then(SipContact::expires)
+        .or_else(|| sip.expires().delta())
+}
+
+impl From<RawEvent> for EventData {
+    fn from(raw: RawEvent) -> Self {
+        let RawEvent {
+            event,
+            status,
+            phrase,
+            handle,
+            sip,
+            tags,
+        } = raw;
+        if let Event::ReplyShutdown = event {
+            return EventData::ReplyShutdown { status, phrase };
+        }
+        let handle = match handle {
+            Some(handle) => handle,
+            None => {
+                return EventData::Raw(RawEvent {
+                    event,
+                    status,
+                    phrase,
+                    handle,
+                    sip,
+                    tags,
+                })
+            }
+        };
+        /* typed only if their tags or their message can be decoded */
+        if let Event::IncomingState = event {
+            if let Some(state) = CallStateInfo::from_tags(&tags) {
+                return EventData::IncomingState { handle, state };
+            }
+        }
+        if let Event::IncomingNotify = event {
+            if let Some(frag) = refer_progress(&sip) {
+                return EventData::ReferProgress {
+                    handle,
+                    status: frag.status(),
+                    phrase: frag.phrase().clone(),
+                };
+            }
+        }
+        match event {
+            Event::IncomingInvite => EventData::IncomingInvite {
+                handle,
+                request: sip,
+            },
+            Event::IncomingCancel => EventData::IncomingCancel { handle },
+            Event::IncomingBye => EventData::IncomingBye {
+                handle,
+                request: sip,
+            },
+            Event::IncomingMessage => EventData::IncomingMessage {
+                handle,
+                request: sip,
+            },
+            Event::IncomingRegister => EventData::IncomingRegister {
+                handle,
+                request: sip,
+            },
+            Event::IncomingActive => EventData::IncomingActive { handle },
+            Event::IncomingTerminated => EventData::IncomingTerminated { handle },
+            Event::ReplyInvite => EventData::ReplyInvite {
+                handle,
+                status,
+                phrase,
+                response: sip,
+            },
+            Event::ReplyBye => EventData::ReplyBye {
+                handle,
+                status,
+                phrase,
+            },
+            Event::ReplyMessage => EventData::ReplyMessage {
+                handle,
+                status,
+                phrase,
+                response: sip,
+            },
+            Event::ReplyRegister => EventData::ReplyRegister {
+                expires: registration_expires(&handle, &sip),
+                contacts: sip.contacts().to_vec(),
+                handle,
+                status,
+                phrase,
+            },
+            Event::ReplyUnregister => EventData::ReplyUnregister {
+                handle,
+                status,
+                phrase,
+            },
+            event => EventData::Raw(RawEvent {
+                event,
+                status,
+                phrase,
+                handle: Some(handle),
+                sip,
+                tags,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_state_info_from_tags() {
+        let tags = vec![
+            Tag::NuCallstate(CallState::Received),
+            Tag::NuOfferRecv(true),
+            Tag::SoaUserSdpStr("v=0".to_string()),
+        ];
+        let info = CallStateInfo::from_tags(&tags).unwrap();
+        assert_eq!(info.state, CallState::Received);
+        assert!(info.offer_recv);
+        assert!(!info.offer_sent && !info.answer_sent && !info.answer_recv);
+
+        assert_eq!(CallStateInfo::from_tags(&[Tag::NuOfferSent(true)]), None);
+    }
+
+    #[test]
+    fn test_event_data_without_handle_is_raw() {
+        let raw = RawEvent {
+            event: Event::IncomingMessage,
+            status: 100,
+            phrase: "Trying".to_string(),
+            handle: None,
+            sip: Sip::default(),
+            tags: vec![],
+        };
+        let data: EventData = raw.into();
+        assert!(matches!(data, EventData::Raw(RawEvent { status: 100, .. })));
+
+        let raw = RawEvent {
+            event: Event::ReplyShutdown,
+            status: 200,
+            phrase: "Shutdown successful".to_string(),
+            handle: None,
+            sip: Sip::default(),
+            tags: vec![],
+        };
+        let data: EventData = raw.into();
+        assert!(matches!(data, EventData::ReplyShutdown { status: 200, .. }));
+    }
+}
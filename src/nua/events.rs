@@ -0,0 +1,108 @@
+use crate::nua::event::RawEvent;
+use crate::su::Root;
+use crate::sys;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+/* maximum time (ms) waiting inside the reactor before checking the queue again */
+pub(crate) const STEP_TIMEOUT: i64 = 100;
+
+pub(crate) type EventQueue = Rc<RefCell<VecDeque<RawEvent>>>;
+
+/// Receiver of the events of a NUA stack, see [`Nua::events`](crate::nua::Nua::events).
+///
+/// The events are queued when the reactor runs; the receiving methods run
+/// the reactor (`su_root_step`) while the queue is empty. As an iterator it
+/// blocks until the next event and ends when the NUA stack is destroyed.
+#[derive(Debug)]
+pub struct Events {
+    queue: EventQueue,
+    pub(crate) root: *mut sys::su_root_t,
+    /* cleared when the NUA stack is destroyed */
+    pub(crate) nua_alive: Rc<Cell<bool>>,
+}
+
+impl Events {
+    pub(crate) fn _new(
+        queue: EventQueue,
+        root: *mut sys::su_root_t,
+        nua_alive: Rc<Cell<bool>>,
+    ) -> Self {
+        Events {
+            queue,
+            root,
+            nua_alive,
+        }
+    }
+
+    pub(crate) fn pop(&self) -> Option<RawEvent> {
+        self.queue.borrow_mut().pop_front()
+    }
+
+    /// Number of events already queued.
+    pub fn len(&self) -> usize {
+        self.queue.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.borrow().is_empty()
+    }
+
+    /// Get the next event, running the reactor once without waiting if
+    /// none is queued.
+    pub fn try_recv(&self) -> Option<RawEvent> {
+        if let Some(event) = self.pop() {
+            return Some(event);
+        }
+        if !self.nua_alive.get() {
+            return None;
+        }
+        Root::_step(self.root, 0);
+        self.pop()
+    }
+
+    /// Wait for the next event, running the reactor up to `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<RawEvent> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(event) = self.pop() {
+                return Some(event);
+            }
+            if !self.nua_alive.get() {
+                return None;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            let left = (deadline - now).as_millis() as i64;
+            Root::_step(self.root, left.min(STEP_TIMEOUT));
+        }
+    }
+
+    /// Wait for the next event, `None` once the NUA stack is destroyed.
+    pub fn recv(&self) -> Option<RawEvent> {
+        loop {
+            if let Some(event) = self.pop() {
+                return Some(event);
+            }
+            if !self.nua_alive.get() {
+                return None;
+            }
+            Root::_step(self.root, STEP_TIMEOUT);
+        }
+    }
+}
+
+impl Iterator for Events {
+    type Item = RawEvent;
+
+    fn next(&mut self) -> Option<RawEvent> {
+        self.recv()
+    }
+}
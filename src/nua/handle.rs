@@ -1,30 +1,135 @@
+use crate::dtmf::Dtmf;
+use crate::dtmf::DTMF_RELAY_CONTENT_TYPE;
 use crate::error::Error;
+use crate::nua::auth::CredentialProvider;
+use crate::nua::event::RawEvent;
+use crate::nua::events::STEP_TIMEOUT;
+use crate::nua::Event;
 use crate::nua::Nua;
 use crate::result::Result;
+use crate::sip::Sip;
+use crate::su::Root;
 use crate::sys;
 use crate::tag::builder::Builder;
+use crate::tag::tag::TagItem;
 use crate::tag::Tag;
 
+use std::any::Any;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::rc::Rc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
 /// NUA transaction handle.
-#[derive(Debug)]
-pub struct Handle<'a> {
-    pub(crate) nua: Option<&'a Nua<'a>>,
-    pub(crate) c_ptr: *mut sys::nua_handle_t,
-    terminate_completed: bool,
+///
+/// Cloning a handle is cheap: all clones share the same operation.
+///
+/// A handle created by [`Handle::create`] is destroyed when the last clone
+/// is dropped. A handle created by the stack for an incoming request (the
+/// one given to the callbacks) is kept by its [`Nua`]: it remains alive
+/// after the callback returns, until [`Handle::destroy`] is called, its
+/// call or subscription is terminated (`IncomingTerminated`), the request
+/// is answered if it is outside of a dialog (eg: MESSAGE, OPTIONS) or the
+/// stack is destroyed. Keep a clone to use it afterwards.
+///
+/// Once the handle is destroyed, or its [`Nua`] is dropped, the requests
+/// sent on it are ignored and [`Handle::respond`] fails with
+/// [`Error::HandleDestroyedError`].
+#[derive(Debug, Clone)]
+pub struct Handle {
+    pub(crate) inner: Rc<HandleInner>,
+}
+
+/// Callback receiving the events of a single handle, see [`Handle::callback`].
+pub type HandleClosure = dyn Fn(&mut Nua, Event, u32, String, &Handle, Sip, Vec<Tag>);
+
+/// State shared by all clones of a handle, it is the `hmagic` of the C handle.
+pub(crate) struct HandleInner {
+    pub(crate) c_ptr: Cell<*mut sys::nua_handle_t>,
+    /* cleared when the NUA stack is destroyed */
+    pub(crate) nua_alive: Rc<Cell<bool>>,
+    /* reactor of the NUA stack, used to wait for the call termination and
+    the destruction; only used while `nua_alive`: the `Root` is borrowed by
+    the `Nua` for its whole lifetime, so it outlives the stack */
+    pub(crate) root: *mut sys::su_root_t,
+    pub(crate) terminate_completed: Cell<bool>,
+    /* created by the stack for a request outside of a dialog (MESSAGE,
+    OPTIONS, ...), released by the `Nua` once the request is answered */
+    pub(crate) outside_dialog: Cell<bool>,
+    pub(crate) answered: Cell<bool>,
+    /* challenges answered automatically since the last final response */
+    pub(crate) auth_attempts: Cell<u32>,
+    /* URL of the Contact given to `Handle::register`, to find its binding
+    in the responses */
+    pub(crate) register_contact: RefCell<Option<String>>,
+    /* requests waiting for their final response (see `Handle::_wait_reply`) */
+    waiters: RefCell<Vec<(Event, ReplySlot)>>,
+    closure: RefCell<Option<Rc<HandleClosure>>>,
+    user_data: RefCell<Option<Rc<dyn Any>>>,
+}
+
+impl std::fmt::Debug for HandleInner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.debug_struct("HandleInner")
+            .field("c_ptr", &self.c_ptr)
+            .field("nua_alive", &self.nua_alive)
+            .field("root", &self.root)
+            .field("terminate_completed", &self.terminate_completed)
+            .field("has_callback", &self.closure.borrow().is_some())
+            .field("has_user_data", &self.user_data.borrow().is_some())
+            .finish()
+    }
 }
 
-impl<'a> Handle<'a> {
-    pub(crate) fn _new() -> Handle<'a> {
+/* C function sending a request on a handle, eg: `nua_invite` */
+type NuaRequest =
+    unsafe extern "C" fn(*mut sys::nua_handle_t, sys::tag_type_t, sys::tag_value_t, ...);
+
+/// Final response of a request, filled when it is received.
+pub(crate) type ReplySlot = Rc<RefCell<Option<RawEvent>>>;
+
+/// Two handles are equal if they are clones of the same operation handle.
+impl PartialEq for Handle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Handle {
+    pub(crate) fn _new(nua: &Nua) -> Handle {
+        Self::_from_parts(nua.alive().clone(), nua.root().c_ptr)
+    }
+
+    /* a handle not bound to the stack yet (null c_ptr) */
+    pub(crate) fn _from_parts(nua_alive: Rc<Cell<bool>>, root: *mut sys::su_root_t) -> Handle {
         Handle {
-            nua: None,
-            c_ptr: std::ptr::null_mut(),
-            terminate_completed: false,
+            inner: Rc::new(HandleInner {
+                c_ptr: Cell::new(std::ptr::null_mut()),
+                nua_alive,
+                root,
+                terminate_completed: Cell::new(false),
+                outside_dialog: Cell::new(false),
+                answered: Cell::new(false),
+                auth_attempts: Cell::new(0),
+                register_contact: RefCell::new(None),
+                waiters: RefCell::new(Vec::new()),
+                closure: RefCell::new(None),
+                user_data: RefCell::new(None),
+            }),
         }
     }
+
     /// Create an operation handle.
-    pub fn create(nua: &'a Box<Nua<'_>>, tags: &[Tag]) -> Result<Box<Handle<'a>>> {
-        let mut handle = Box::new(Self::_new());
-        let handle_ptr = &mut *handle as *mut Handle as *mut sys::nua_hmagic_t;
+    pub fn create(nua: &Nua, tags: &[Tag]) -> Result<Handle> {
+        let handle = Self::_new(nua);
+        let handle_ptr = Rc::as_ptr(&handle.inner) as *mut sys::nua_hmagic_t;
 
         /* Convert &[Tag] -> &[TagItem] -> &[sys::tagi_t] */
         /* Intermediate Vec<TagItem> is necessary to hold c pointers when we call create */
@@ -33,11 +138,144 @@ impl<'a> Handle<'a> {
         let sys_tags = sys_tags.as_slice();
 
         let magic = handle_ptr;
-        handle.c_ptr = Handle::_create(nua.c_ptr, magic, Some(sys_tags))?;
-        handle.nua = Some(nua);
+        let nh = Handle::_create(nua.c_ptr(), magic, Some(sys_tags))?;
+        /* our own reference, released when the last clone is dropped */
+        unsafe { sys::nua_handle_ref(nh) };
+        handle.inner.c_ptr.set(nh);
         Ok(handle)
     }
 
+    /// Adopt an operation handle created by the stack (incoming requests),
+    /// it is kept by `nua` until it is destroyed.
+    pub(crate) fn _adopt(nua: &Nua, nh: *mut sys::nua_handle_t) -> Handle {
+        assert!(!nh.is_null());
+        let handle = Self::_new(nua);
+        let magic = Rc::as_ptr(&handle.inner) as *mut sys::nua_hmagic_t;
+        unsafe {
+            sys::nua_handle_bind(nh, magic);
+            sys::nua_handle_ref(nh);
+        };
+        handle.inner.c_ptr.set(nh);
+        nua._keep_adopted(&handle);
+        handle
+    }
+
+    /// Get a new clone of the handle pointed by `hmagic`.
+    ///
+    /// # Safety
+    /// `hmagic` must have been bound by [`Handle::create`] or [`Handle::_adopt`]
+    /// and the handle must not be destroyed yet.
+    pub(crate) unsafe fn _from_hmagic(hmagic: *mut sys::nua_hmagic_t) -> Handle {
+        assert!(!hmagic.is_null());
+        let inner = hmagic as *const HandleInner;
+        Rc::increment_strong_count(inner);
+        Handle {
+            inner: Rc::from_raw(inner),
+        }
+    }
+
+    /// Get a slot filled with the next final response reported by `event`
+    /// (eg: `ReplyInvite`) on this handle.
+    pub(crate) fn _wait_reply(&self, event: Event) -> ReplySlot {
+        let slot = ReplySlot::default();
+        self.inner.waiters.borrow_mut().push((event, slot.clone()));
+        slot
+    }
+
+    /* fill the slots waiting for the final response reported by `raw` */
+    pub(crate) fn _on_reply(&self, raw: &RawEvent) {
+        if raw.status < 200 {
+            return;
+        }
+        self.inner.waiters.borrow_mut().retain(|(event, slot)| {
+            if *event != raw.event {
+                return true;
+            }
+            slot.replace(Some(raw.clone()));
+            false
+        });
+    }
+
+    /* some slot waits for the final response reported by `event` */
+    pub(crate) fn _has_waiters(&self, event: &Event) -> bool {
+        self.inner
+            .waiters
+            .borrow()
+            .iter()
+            .any(|(waited, _)| waited == event)
+    }
+
+    /// Deliver the events of this handle to `cb` instead of the callback
+    /// (or the receiver) of its NUA stack.
+    ///
+    /// The closure is kept until the handle is destroyed: to avoid a
+    /// reference cycle it should use its `&Handle` argument rather than
+    /// capture a clone of this handle.
+    pub fn callback<F: Fn(&mut Nua, Event, u32, String, &Handle, Sip, Vec<Tag>) + 'static>(
+        &self,
+        cb: F,
+    ) {
+        self.inner.closure.replace(Some(Rc::new(cb)));
+    }
+
+    /// Remove the callback set by [`Handle::callback`], the events go back
+    /// to the NUA stack.
+    pub fn remove_callback(&self) {
+        self.inner.closure.replace(None);
+    }
+
+    pub(crate) fn _callback(&self) -> Option<Rc<HandleClosure>> {
+        self.inner.closure.borrow().clone()
+    }
+
+    /// Attach application data to the handle (shared by all its clones),
+    /// eg: the call object of a dialog.
+    ///
+    /// Use interior mutability (`RefCell`, `Cell`) for mutable state.
+    pub fn set_user_data<T: Any>(&self, data: T) {
+        self.inner.user_data.replace(Some(Rc::new(data)));
+    }
+
+    /// Application data attached by [`Handle::set_user_data`], `None` if
+    /// there is none or it is not a `T`.
+    pub fn user_data<T: Any>(&self) -> Option<Rc<T>> {
+        let data = self.inner.user_data.borrow().clone()?;
+        data.downcast::<T>().ok()
+    }
+
+    /// Detach the application data of the handle.
+    pub fn take_user_data<T: Any>(&self) -> Option<Rc<T>> {
+        let data = self.inner.user_data.borrow_mut().take()?;
+        data.downcast::<T>().ok()
+    }
+
+    /* null once the handle is destroyed or its stack is gone (the memory of
+    the C handle is released with the stack) */
+    pub(crate) fn c_ptr(&self) -> *mut sys::nua_handle_t {
+        if !self.inner.nua_alive.get() {
+            return std::ptr::null_mut();
+        }
+        self.inner.c_ptr.get()
+    }
+
+    /* send a request (`f` is eg: `nua_invite`) with `tags` on the C handle,
+    ignored once the handle is destroyed */
+    fn call(&self, f: NuaRequest, tags: &[Tag]) {
+        /* Convert &[Tag] -> &[TagItem] -> &[sys::tagi_t] */
+        self.call_items(f, Builder::_create_vec_tag_items(tags))
+    }
+
+    fn call_items(&self, f: NuaRequest, tag_items: Vec<TagItem>) {
+        let nh = self.c_ptr();
+        if nh.is_null() {
+            return;
+        }
+        /* Intermediate Vec<TagItem> is necessary to hold c pointers (avoid be freed) when we call `f` */
+        let sys_tags = Builder::_create_vec_sys_tags(&tag_items);
+        /* TAG_NEXT */
+        unsafe { f(nh, sys::tag_next.as_ptr(), sys_tags.as_ptr() as isize) };
+    }
+
     pub(crate) fn _create(
         nua: *mut sys::nua_t,
         magic: *mut sys::nua_hmagic_t,
@@ -72,67 +310,279 @@ impl<'a> Handle<'a> {
         Ok(handle_sys)
     }
 
-    pub(crate) fn _message(nh: *mut sys::nua_handle_t, tags: Option<&[sys::tagi_t]>) {
-        let tag_name: *const sys::tag_type_s;
-        let tag_value: isize;
+    /// Send an instant message.
+    pub fn message(&self, tags: &[Tag]) {
+        self.call(sys::nua_message, tags)
+    }
 
-        assert!(!nh.is_null());
+    /// Place a call using SIP INVITE method.
+    pub fn invite(&self, tags: &[Tag]) {
+        self.call(sys::nua_invite, tags)
+    }
 
-        // dbg!(tags);
+    /// Send the INVITE of a call referred by `referrer`, the handle that
+    /// received the REFER. Its progress is notified to the referrer; pass
+    /// the `Tag::NuReferEvent` of `IncomingRefer` in `tags`.
+    pub fn invite_referred(&self, referrer: &Handle, tags: &[Tag]) {
+        let mut tag_items = vec![TagItem::NuNotifyRefer(referrer.clone())];
+        tag_items.extend(Builder::_create_vec_tag_items(tags));
+        self.call_items(sys::nua_invite, tag_items)
+    }
 
-        if tags.is_none() {
-            /* TAG_NULL */
-            tag_name = std::ptr::null();
-            tag_value = 0;
-        } else {
-            /* TAG_NEXT */
-            tag_name = unsafe { sys::tag_next.as_ptr() };
-            tag_value = tags.unwrap().as_ptr() as isize;
+    /// Hang up the call using SIP BYE method.
+    pub fn bye(&self, tags: &[Tag]) {
+        self.call(sys::nua_bye, tags)
+    }
+
+    /// Cancel a pending request (INVITE) using SIP CANCEL method.
+    pub fn cancel(&self, tags: &[Tag]) {
+        self.call(sys::nua_cancel, tags)
+    }
+
+    /// Acknowledge a final response to INVITE.
+    ///
+    /// Only needed when the stack was created with automatic ACK disabled.
+    pub fn ack(&self, tags: &[Tag]) {
+        self.call(sys::nua_ack, tags)
+    }
+
+    /// Register to a registrar using SIP REGISTER method.
+    ///
+    /// Use `siptag_contact`, `siptag_expires` and `nutag_outbound_options` to
+    /// give the binding, its expiry and how the stack keeps it reachable.
+    /// The stack refreshes the registration automatically before it expires,
+    /// each refresh is reported as a new `ReplyRegister` event.
+    pub fn register(&self, tags: &[Tag]) {
+        let contact = tags.iter().rev().find_map(|tag| match tag {
+            Tag::SipContactStr(contact) => Some(contact_url(contact).to_string()),
+            _ => None,
+        });
+        if contact.is_some() {
+            self.inner.register_contact.replace(contact);
         }
-        unsafe { sys::nua_message(nh, tag_name, tag_value) };
+        self.call(sys::nua_register, tags)
     }
-    /// Send an instant message.
-    pub fn message(&self, tags: &[Tag]) {
-        /* Convert &[Tag] -> &[TagItem] -> &[sys::tagi_t] */
-        /* Intermediate Vec<TagItem> is necessary to hold c pointers (avoid be freed) when we call create */
-        let tag_items = Builder::_create_vec_tag_items(tags);
-        let sys_tags = Builder::_create_vec_sys_tags(&tag_items);
-        let sys_tags = sys_tags.as_slice();
 
-        let nh = self.c_ptr;
-        Self::_message(nh, Some(sys_tags))
+    /// Remove the registration done by `register` (REGISTER with expires 0).
+    pub fn unregister(&self, tags: &[Tag]) {
+        self.call(sys::nua_unregister, tags)
     }
 
-    pub(crate) fn _invite(nh: *mut sys::nua_handle_t, tags: Option<&[sys::tagi_t]>) {
-        let tag_name: *const sys::tag_type_s;
-        let tag_value: isize;
+    /// Subscribe to a SIP event using SIP SUBSCRIBE method.
+    ///
+    /// Use `siptag_event_str`, `siptag_accept_str` and `siptag_expires` to select
+    /// the event package, the accepted payloads and the subscription duration.
+    /// The stack refreshes the subscription automatically before it expires.
+    /// Each NOTIFY is reported as an `IncomingNotify` event.
+    pub fn subscribe(&self, tags: &[Tag]) {
+        self.call(sys::nua_subscribe, tags)
+    }
 
-        assert!(!nh.is_null());
+    /// Unsubscribe an event using SIP SUBSCRIBE method with `Expires: 0`.
+    pub fn unsubscribe(&self, tags: &[Tag]) {
+        self.call(sys::nua_unsubscribe, tags)
+    }
 
-        // dbg!(tags);
+    /// Create an event server (notifier) for an event package.
+    ///
+    /// Use `siptag_event_str`, `siptag_content_type_str` and `siptag_payload_str`
+    /// to describe the event and its current state. Calling it again with a new
+    /// payload updates the state and sends a NOTIFY to all active watchers.
+    /// Use `nutag_substate(SubscriptionState::Pending)` to require each new
+    /// watcher to be authorized (see `authorize`).
+    pub fn notifier(&self, tags: &[Tag]) {
+        self.call(sys::nua_notifier, tags)
+    }
 
-        if tags.is_none() {
-            /* TAG_NULL */
-            tag_name = std::ptr::null();
-            tag_value = 0;
-        } else {
-            /* TAG_NEXT */
-            tag_name = unsafe { sys::tag_next.as_ptr() };
-            tag_value = tags.unwrap().as_ptr() as isize;
+    /// Authorize a watcher reported by an `IncomingSubscription` event.
+    ///
+    /// Pass back the `Tag::NeaSub` received with the event together with
+    /// `nutag_substate` to accept or reject the watcher.
+    ///
+    /// # Safety
+    /// The stack uses the watcher given by `Tag::NeaSub` without checking
+    /// it: it must have been reported on this handle and must still be
+    /// alive, ie: no `IncomingSubscription` reported it as terminated and
+    /// the notifier was not terminated since. Authorizing it from the
+    /// callback of the event that reported it is always safe.
+    pub unsafe fn authorize(&self, tags: &[Tag]) {
+        self.call(sys::nua_authorize, tags)
+    }
+
+    /// Send a SIP NOTIFY request on a subscription accepted on this handle.
+    pub fn notify(&self, tags: &[Tag]) {
+        self.call(sys::nua_notify, tags)
+    }
+
+    /// Terminate the event server created with `notifier`, all watchers are
+    /// notified that their subscriptions are terminated.
+    pub fn terminate(&self, tags: &[Tag]) {
+        self.call(sys::nua_terminate, tags)
+    }
+
+    /// Publish an event state using SIP PUBLISH method.
+    ///
+    /// Use `siptag_event_str`, `siptag_content_type_str`, `siptag_payload_str`
+    /// and `siptag_expires` to describe the publication. The stack keeps the
+    /// SIP-ETag returned by the server and sends it back as SIP-If-Match when
+    /// the publication is refreshed, modified (by calling `publish` again) or
+    /// removed (see `unpublish`).
+    pub fn publish(&self, tags: &[Tag]) {
+        self.call(sys::nua_publish, tags)
+    }
+
+    /// Remove a publication using SIP PUBLISH method with `Expires: 0`.
+    pub fn unpublish(&self, tags: &[Tag]) {
+        self.call(sys::nua_unpublish, tags)
+    }
+
+    /// Transfer a call using SIP REFER method.
+    ///
+    /// Use `siptag_refer_to_str` to set the transfer target. The progress of
+    /// the referred call is reported by the other party with NOTIFY requests,
+    /// each one is reported as an `IncomingNotify` event (see `Sip::sipfrag`),
+    /// or as `EventData::ReferProgress` with [`Nua::event_callback`].
+    pub fn refer(&self, tags: &[Tag]) {
+        self.call(sys::nua_refer, tags)
+    }
+
+    /// Send a SIP INFO request within a call.
+    pub fn info(&self, tags: &[Tag]) {
+        self.call(sys::nua_info, tags)
+    }
+
+    /// Send a DTMF event using SIP INFO method with an `application/dtmf-relay` body.
+    pub fn info_dtmf(&self, dtmf: &Dtmf, tags: &[Tag]) {
+        let mut tags = tags.to_vec();
+        tags.push(Tag::SipContentTypeStr(DTMF_RELAY_CONTENT_TYPE.to_string()));
+        tags.push(Tag::SipPayloadStr(dtmf.to_dtmf_relay()));
+        self.info(&tags)
+    }
+
+    /// Acknowledge a reliable provisional response using SIP PRACK method.
+    ///
+    /// The stack sends PRACK automatically unless `nutag_autoprack(false)` is used,
+    /// in that case call it after a reliable `ReplyInvite` (eg: 183).
+    pub fn prack(&self, tags: &[Tag]) {
+        self.call(sys::nua_prack, tags)
+    }
+
+    /// Update the session parameters (eg: SDP) of a call using SIP UPDATE method.
+    ///
+    /// It is also used by the stack to refresh the session when session timers
+    /// are enabled with `nutag_update_refresh(true)`.
+    pub fn update(&self, tags: &[Tag]) {
+        self.call(sys::nua_update, tags)
+    }
+
+    /// Query the capabilities of the remote party using SIP OPTIONS method.
+    ///
+    /// See also [`Qualifier`](crate::nua::Qualifier) to check the reachability of
+    /// a list of URIs periodically.
+    pub fn options(&self, tags: &[Tag]) {
+        self.call(sys::nua_options, tags)
+    }
+
+    /// Send a request with an extension method (eg: `FOO`).
+    ///
+    /// The remote stack must allow it (`nutag_allow`) and, to answer it from
+    /// the application, list it in `nutag_appl_method`. The request is then
+    /// reported as an `IncomingMethod` event.
+    pub fn method(&self, name: &str, tags: &[Tag]) {
+        let mut tags = tags.to_vec();
+        tags.insert(0, Tag::NuMethod(name.to_string()));
+
+        self.call(sys::nua_method, &tags)
+    }
+
+    /// Add credentials (`nutag_auth`) to the request challenged with a 401 or 407
+    /// response and restart it.
+    ///
+    /// A failure is reported as a `ReplyAuthenticate` event.
+    pub fn authenticate(&self, tags: &[Tag]) {
+        self.call(sys::nua_authenticate, tags)
+    }
+
+    /// Answer the challenges of a 401 or 407 response (`sip`) with the
+    /// credentials given by `provider` for each realm and restart the request.
+    ///
+    /// Return `false` if no challenge could be answered.
+    pub fn authenticate_with<P: CredentialProvider + ?Sized>(
+        &self,
+        sip: &Sip,
+        provider: &P,
+    ) -> bool {
+        let tags: Vec<Tag> = sip
+            .www_authenticate()
+            .iter()
+            .chain(sip.proxy_authenticate().iter())
+            .filter_map(|challenge| {
+                let credentials = provider.credentials(&challenge.realm()?)?;
+                credentials.nutag_auth(challenge)
+            })
+            .map(Tag::NuAuth)
+            .collect();
+        if tags.is_empty() {
+            return false;
         }
-        unsafe { sys::nua_invite(nh, tag_name, tag_value) };
+        self.authenticate(&tags);
+        true
     }
 
-    /// Place a call using SIP INVITE method.
-    pub fn invite(&self, tags: &[Tag]) {
-        /* Convert &[Tag] -> &[TagItem] -> &[sys::tagi_t] */
-        /* Intermediate Vec<TagItem> is necessary to hold c pointers (avoid be freed) when we call create */
+    /// Respond to an incoming request (INVITE, MESSAGE, ...).
+    pub fn respond(&self, status: u32, phrase: &str, tags: &[Tag]) -> Result<()> {
+        let phrase = CString::new(phrase)?;
+
+        let nh = self.c_ptr();
+        if nh.is_null() {
+            return Err(Error::HandleDestroyedError);
+        }
+        /* Intermediate Vec<TagItem> is necessary to hold c pointers (avoid be freed) when we call respond */
         let tag_items = Builder::_create_vec_tag_items(tags);
         let sys_tags = Builder::_create_vec_sys_tags(&tag_items);
-        let sys_tags = sys_tags.as_slice();
+        unsafe {
+            sys::nua_respond(
+                nh,
+                status as std::os::raw::c_int,
+                phrase.as_ptr(),
+                sys::tag_next.as_ptr(),
+                sys_tags.as_ptr() as isize,
+            )
+        };
+        if status >= 200 {
+            self.inner.answered.set(true);
+        }
+        Ok(())
+    }
 
-        let nh = self.c_ptr;
-        Self::_invite(nh, Some(sys_tags))
+    /// Respond reliably (RFC 3262) to an incoming INVITE with
+    /// 183 Session Progress carrying `sdp` for early media.
+    ///
+    /// The INVITE must support `100rel`, the answer to the PRACK is reported
+    /// as an `IncomingPrack` event.
+    pub fn respond_session_progress(&self, sdp: &str, tags: &[Tag]) -> Result<()> {
+        let mut tags = tags.to_vec();
+        tags.push(Tag::SipRequireStr("100rel".to_string()));
+        tags.push(Tag::SoaUserSdpStr(sdp.to_string()));
+
+        self.respond(183, "Session Progress", &tags)
+    }
+
+    /// Accept an incoming publication (`IncomingPublish` event).
+    ///
+    /// A new entity tag is generated and sent as SIP-ETag in a 200 response
+    /// together with `Expires`. The entity tag is returned, the application
+    /// must keep it to match the SIP-If-Match of the next refresh,
+    /// modification or removal of that publication.
+    pub fn respond_publish(&self, expires: u32, tags: &[Tag]) -> Result<String> {
+        let etag = generate_etag();
+
+        let mut tags = tags.to_vec();
+        tags.push(Tag::SipEtagStr(etag.clone()));
+        tags.push(Tag::SipExpiresStr(expires.to_string()));
+
+        self.respond(200, "OK", &tags)?;
+        Ok(etag)
     }
 
     pub(crate) fn _destroy(nh: *mut sys::nua_handle_t) {
@@ -142,17 +592,304 @@ impl<'a> Handle<'a> {
         };
     }
 
-    pub(crate) fn destroy(&mut self) {
-        if self.c_ptr.is_null() {
+    pub(crate) fn _has_active_call(nh: *mut sys::nua_handle_t) -> bool {
+        assert!(!nh.is_null());
+        unsafe { sys::nua_handle_has_active_call(nh) != 0 }
+    }
+
+    /// Check if the handle has an active call.
+    pub fn has_active_call(&self) -> bool {
+        let nh = self.c_ptr();
+        !nh.is_null() && Self::_has_active_call(nh)
+    }
+
+    pub(crate) fn _has_call_on_hold(nh: *mut sys::nua_handle_t) -> bool {
+        assert!(!nh.is_null());
+        unsafe { sys::nua_handle_has_call_on_hold(nh) != 0 }
+    }
+
+    /// Check if the handle has a call on hold.
+    pub fn has_call_on_hold(&self) -> bool {
+        let nh = self.c_ptr();
+        !nh.is_null() && Self::_has_call_on_hold(nh)
+    }
+
+    /// Build the Replaces header value (RFC 3891) of the call on this handle,
+    /// to be used in an INVITE (`siptag_replaces_str`) or in a Refer-To URI.
+    ///
+    /// Return `None` if there is no dialog on this handle.
+    pub fn make_replaces(&self, early_only: bool) -> Option<String> {
+        let nh = self.c_ptr();
+        if nh.is_null() {
+            return None;
+        }
+        let home = unsafe { sys::su_home_new(std::mem::size_of::<sys::su_home_t>() as _) }
+            as *mut sys::su_home_t;
+        assert!(!home.is_null());
+
+        let mut replaces = None;
+        let rp = unsafe { sys::nua_handle_make_replaces(nh, home, early_only as i32) };
+        if !rp.is_null() {
+            let s = unsafe { sys::msg_header_as_string(home, rp as *const sys::msg_header_t) };
+            if !s.is_null() {
+                replaces = Some(unsafe { CStr::from_ptr(s).to_string_lossy().into_owned() });
+            }
+        }
+        unsafe { sys::su_home_unref(home) };
+        replaces
+    }
+
+    /// Find the handle of the call identified by a Replaces header value,
+    /// as received in an INVITE (`Sip::replaces`).
+    pub fn by_replaces(nua: &Nua, replaces: &str) -> Result<Option<Handle>> {
+        let replaces = CString::new(replaces)?;
+        let home = unsafe { sys::su_home_new(std::mem::size_of::<sys::su_home_t>() as _) }
+            as *mut sys::su_home_t;
+        assert!(!home.is_null());
+
+        let mut handle = None;
+        let rp = unsafe {
+            sys::msg_header_make(
+                home,
+                sys::sip_replaces_class.as_mut_ptr(),
+                replaces.as_ptr(),
+            )
+        } as *const sys::sip_replaces_t;
+        if !rp.is_null() {
+            /* a new reference is returned */
+            let nh = unsafe { sys::nua_handle_by_replaces(nua.c_ptr(), rp) };
+            if !nh.is_null() {
+                let hmagic = unsafe { sys::nua_handle_magic(nh) };
+                handle = Some(if hmagic.is_null() {
+                    Self::_adopt(nua, nh)
+                } else {
+                    unsafe { Self::_from_hmagic(hmagic) }
+                });
+                unsafe { sys::nua_handle_unref(nh) };
+            }
+        }
+        unsafe { sys::su_home_unref(home) };
+        Ok(handle)
+    }
+
+    /// Hang up the call (if any), wait up to `timeout` for its termination
+    /// (`ReplyBye` or `IncomingTerminated`), then destroy the handle.
+    ///
+    /// The handle is destroyed even if the call did not terminate in time,
+    /// [`Error::TimeoutError`] is returned in that case. The stack completes
+    /// the destruction without reporting it to the application.
+    pub fn destroy_and_wait(&self, timeout: Duration) -> Result<()> {
+        if self.c_ptr().is_null() {
+            return Ok(());
+        }
+        let mut result = Ok(());
+        if self.has_active_call() && !self.inner.terminate_completed.get() {
+            self.bye(&[]);
+            let deadline = Instant::now() + timeout;
+            while !self.inner.terminate_completed.get() && self.inner.nua_alive.get() {
+                let now = Instant::now();
+                if now >= deadline {
+                    result = Err(Error::TimeoutError);
+                    break;
+                }
+                let left = (deadline - now).as_millis() as i64;
+                Root::_step(self.inner.root, left.min(STEP_TIMEOUT));
+            }
+        }
+        self.destroy();
+        result
+    }
+
+    /// Destroy the operation handle, shared by all clones.
+    ///
+    /// It is done automatically when the last clone of a handle created by
+    /// [`Handle::create`] is dropped, the handles of incoming requests must
+    /// be destroyed explicitly (or they are destroyed with the stack).
+    pub fn destroy(&self) {
+        self.inner.destroy()
+    }
+}
+
+impl HandleInner {
+    pub(crate) fn destroy(&self) {
+        let nh = self.c_ptr.replace(std::ptr::null_mut());
+        if nh.is_null() {
+            return;
+        }
+        /* break cycles through the closure or the user data */
+        self.closure.replace(None);
+        self.user_data.replace(None);
+        if !self.nua_alive.get() {
+            /* the stack already released every handle */
             return;
         }
-        Self::_destroy(self.c_ptr);
-        self.c_ptr = std::ptr::null_mut();
+        Handle::_destroy(nh);
+        unsafe { sys::nua_handle_unref(nh) };
     }
 }
 
-impl<'a> Drop for Handle<'a> {
+impl Drop for HandleInner {
     fn drop(&mut self) {
         self.destroy()
     }
 }
+
+/* URL of a Contact header value, eg: `sip:alice@host` for
+`"Alice" <sip:alice@host>;expires=60` */
+fn contact_url(contact: &str) -> &str {
+    match (contact.find('<'), contact.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &contact[start + 1..end],
+        _ => contact.split(';').next().unwrap_or_default().trim(),
+    }
+}
+
+/// Generate an entity tag (RFC 3903) unique for this process.
+fn generate_etag() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}{:x}", nanos, count)
+}
+
+#[cfg(test)]
+mod tests {
+    /* no C calls here: these tests can also run with `cargo miri test --lib ownership` */
+    use super::*;
+
+    fn detached() -> Handle {
+        Handle::_from_parts(Rc::new(Cell::new(true)), std::ptr::null_mut())
+    }
+
+    /* bound to a C handle of a stack already destroyed, never dereferenced */
+    fn orphaned() -> Handle {
+        let handle = Handle::_from_parts(Rc::new(Cell::new(false)), std::ptr::null_mut());
+        handle
+            .inner
+            .c_ptr
+            .set(std::ptr::NonNull::dangling().as_ptr());
+        handle
+    }
+
+    fn reply(event: Event, status: u32) -> RawEvent {
+        RawEvent {
+            event,
+            status,
+            phrase: String::new(),
+            handle: None,
+            sip: Sip::default(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ownership_clones_share_the_handle() {
+        let a = detached();
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert_ne!(a, detached());
+        assert_eq!(Rc::strong_count(&a.inner), 2);
+        drop(b);
+        assert_eq!(Rc::strong_count(&a.inner), 1);
+    }
+
+    #[test]
+    fn ownership_from_hmagic() {
+        let a = detached();
+        let hmagic = Rc::as_ptr(&a.inner) as *mut sys::nua_hmagic_t;
+        let b = unsafe { Handle::_from_hmagic(hmagic) };
+        assert_eq!(a, b);
+        assert_eq!(Rc::strong_count(&a.inner), 2);
+        drop(b);
+        assert_eq!(Rc::strong_count(&a.inner), 1);
+    }
+
+    #[test]
+    fn ownership_user_data() {
+        let a = detached();
+        let b = a.clone();
+        assert!(a.user_data::<u32>().is_none());
+        a.set_user_data(RefCell::new(1u32));
+        *b.user_data::<RefCell<u32>>().unwrap().borrow_mut() += 1;
+        assert_eq!(*a.user_data::<RefCell<u32>>().unwrap().borrow(), 2);
+        assert!(a.user_data::<String>().is_none());
+        assert!(b.take_user_data::<RefCell<u32>>().is_some());
+        assert!(a.user_data::<RefCell<u32>>().is_none());
+    }
+
+    #[test]
+    fn ownership_callback() {
+        let a = detached();
+        assert!(a._callback().is_none());
+        a.callback(|_, _, _, _, _, _, _| {});
+        assert!(a.clone()._callback().is_some());
+        a.remove_callback();
+        assert!(a._callback().is_none());
+    }
+
+    #[test]
+    fn ownership_destroy_releases_closure_and_user_data() {
+        let data = Rc::new(());
+        let a = orphaned();
+        let b = a.clone();
+        a.set_user_data(data.clone());
+        let captured = data.clone();
+        a.callback(move |_, _, _, _, _, _, _| {
+            let _ = &captured;
+        });
+        assert_eq!(Rc::strong_count(&data), 3);
+        a.destroy();
+        assert!(a.inner.c_ptr.get().is_null());
+        /* released even if a clone is still alive */
+        assert_eq!(Rc::strong_count(&data), 1);
+        assert!(b._callback().is_none());
+    }
+
+    #[test]
+    fn ownership_orphaned_handle_is_not_used() {
+        let a = orphaned();
+        assert!(a.c_ptr().is_null());
+        a.message(&[]);
+        a.invite(&[]);
+        a.bye(&[]);
+        assert!(a.destroy_and_wait(Duration::from_secs(1)).is_ok());
+        assert!(!a.has_active_call());
+        assert!(!a.has_call_on_hold());
+        assert!(a.make_replaces(false).is_none());
+        assert!(matches!(
+            a.respond(200, "OK", &[]),
+            Err(Error::HandleDestroyedError)
+        ));
+        /* the C handle went away with the stack */
+        drop(a);
+    }
+
+    #[test]
+    fn ownership_wait_reply() {
+        let a = detached();
+        let invite = a._wait_reply(Event::ReplyInvite);
+        let bye = a._wait_reply(Event::ReplyBye);
+        a._on_reply(&reply(Event::ReplyInvite, 180));
+        assert!(invite.borrow().is_none());
+        a._on_reply(&reply(Event::ReplyInvite, 200));
+        assert_eq!(invite.borrow().as_ref().unwrap().status, 200);
+        assert!(bye.borrow().is_none());
+        /* the slot is filled only once */
+        a._on_reply(&reply(Event::ReplyInvite, 486));
+        assert_eq!(invite.borrow().as_ref().unwrap().status, 200);
+    }
+
+    #[test]
+    fn test_contact_url() {
+        assert_eq!(contact_url("sip:alice@host"), "sip:alice@host");
+        assert_eq!(contact_url("<sip:alice@host>"), "sip:alice@host");
+        assert_eq!(contact_url("sip:alice@host;expires=60"), "sip:alice@host");
+        assert_eq!(
+            contact_url("\"Alice\" <sip:alice@host;transport=tcp>;expires=60"),
+            "sip:alice@host;transport=tcp"
+        );
+    }
+}
@@ -1,8 +1,20 @@
+pub mod auth;
 pub mod event;
+pub mod events;
 pub mod handle;
 pub mod nua;
+pub mod qualify;
 
+pub use crate::nua::auth::CredentialProvider;
+pub use crate::nua::auth::Credentials;
+pub use crate::nua::event::CallStateInfo;
 pub use crate::nua::event::Event;
 pub use crate::nua::event::EventClosure;
+pub use crate::nua::event::EventData;
+pub use crate::nua::event::RawEvent;
+pub use crate::nua::events::Events;
+pub use crate::nua::handle::HandleClosure;
 pub use crate::nua::nua::Handle;
 pub use crate::nua::nua::Nua;
+pub use crate::nua::qualify::Qualifier;
+pub use crate::nua::qualify::QualifyResult;
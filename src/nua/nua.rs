@@ -1,4 +1,7 @@
 use crate::error::Error;
+use crate::nua::auth::CredentialProvider;
+use crate::nua::events::EventQueue;
+use crate::nua::events::Events;
 use crate::result::Result;
 use crate::su;
 use crate::su::Root;
@@ -6,43 +9,121 @@ use crate::sys;
 
 pub use crate::nua::event::Event;
 pub use crate::nua::event::EventClosure;
+pub use crate::nua::event::EventData;
+pub use crate::nua::event::RawEvent;
 pub use crate::nua::handle::Handle;
 use crate::sip::Sip;
+use crate::sip::SipRef;
 use crate::tag::builder::Builder;
 use crate::tag::tag::Tag;
 
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
 use std::convert::TryFrom;
 use std::ffi::CStr;
+use std::rc::Rc;
+
+/* a request challenged again after this many answers is not retried
+(the credentials were rejected), a request may be challenged both by a
+proxy (407) and by the final server (401) */
+const MAX_AUTH_ATTEMPTS: u32 = 2;
+
+type NuaClosure<'a> = dyn Fn(&mut Nua, Event, u32, String, Option<&Handle>, Sip, Vec<Tag>) + 'a;
+type NuaDataClosure<'a> = dyn Fn(&mut Nua, EventData) + 'a;
+type NuaRefClosure<'a> =
+    dyn Fn(&mut Nua, Event, u32, String, Option<&Handle>, SipRef<'_>, Vec<Tag>) + 'a;
 
 ///NUA agent.
+///
+///The stack is destroyed when the `Nua` returned by [`Nua::create`] is dropped,
+///the `&mut Nua` given to the callbacks only borrows it.
 pub struct Nua<'a> {
-    pub(crate) root: Option<&'a su::Root>,
-    pub(crate) c_ptr: *mut sys::nua_t,
-    pub(crate) closure:
-        Option<Box<dyn Fn(&mut Nua, Event, u32, String, Option<&Handle>, Sip, Vec<Tag>) + 'a>>,
-    shutdown_completed: bool,
+    pub(crate) inner: Rc<NuaInner<'a>>,
+    /* only the owner destroys the stack when dropped */
+    owner: bool,
+}
+
+/// State shared by the owner of the stack and the `Nua` given to the
+/// callbacks, it is the `magic` of the C stack.
+///
+/// It is only mutated through `Cell`/`RefCell`: a callback may run while
+/// the application holds a `&Nua` (eg: in [`Nua::shutdown_and_wait`]).
+pub(crate) struct NuaInner<'a> {
+    root: Option<&'a su::Root>,
+    c_ptr: Cell<*mut sys::nua_t>,
+    closure: RefCell<Option<Rc<NuaClosure<'a>>>>,
+    data_closure: RefCell<Option<Rc<NuaDataClosure<'a>>>>,
+    ref_closure: RefCell<Option<Rc<NuaRefClosure<'a>>>>,
+    /* events delivered to an `Events` receiver instead of a closure */
+    queue: RefCell<Option<EventQueue>>,
+    credential_provider: RefCell<Option<Rc<dyn CredentialProvider + 'a>>>,
+    /* handles created by the stack, kept until they are destroyed */
+    adopted: RefCell<Vec<Handle>>,
+    shutdown_completed: Cell<bool>,
+    /* shared with every handle, cleared when the stack is destroyed */
+    alive: Rc<Cell<bool>>,
 }
 
 impl<'a> std::fmt::Debug for Nua<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         f.debug_struct("Nua")
-            .field("Self", &(&*self as *const Nua))
-            .field("c_ptr", &self.c_ptr)
-            .field("root", &self.root)
+            .field("inner", &Rc::as_ptr(&self.inner))
+            .field("c_ptr", &self.c_ptr())
+            .field("root", &self.inner.root)
+            .field("owner", &self.owner)
             .finish()
     }
 }
 
 impl<'a> Nua<'a> {
-    pub(crate) fn _new() -> Nua<'a> {
+    pub(crate) fn _new(root: Option<&'a su::Root>) -> Nua<'a> {
+        Nua {
+            inner: Rc::new(NuaInner {
+                root,
+                closure: RefCell::new(None),
+                data_closure: RefCell::new(None),
+                ref_closure: RefCell::new(None),
+                queue: RefCell::new(None),
+                c_ptr: Cell::new(std::ptr::null_mut()),
+                credential_provider: RefCell::new(None),
+                adopted: RefCell::new(Vec::new()),
+                shutdown_completed: Cell::new(false),
+                alive: Rc::new(Cell::new(true)),
+            }),
+            owner: true,
+        }
+    }
+
+    /// Get a `Nua` sharing the stack pointed by `magic`, it does not destroy
+    /// the stack when dropped.
+    ///
+    /// # Safety
+    /// `magic` must have been given to `nua_create` by [`Nua::create_with_root`]
+    /// and its owner must not be dropped yet.
+    pub(crate) unsafe fn _from_magic(magic: *mut sys::nua_magic_t) -> Nua<'a> {
+        assert!(!magic.is_null());
+        let inner = magic as *const NuaInner;
+        Rc::increment_strong_count(inner);
         Nua {
-            root: None,
-            closure: None,
-            c_ptr: std::ptr::null_mut(),
-            shutdown_completed: false,
+            inner: Rc::from_raw(inner),
+            owner: false,
         }
     }
 
+    pub(crate) fn c_ptr(&self) -> *mut sys::nua_t {
+        self.inner.c_ptr.get()
+    }
+
+    /* cleared when the stack is destroyed, shared with every handle */
+    pub(crate) fn alive(&self) -> &Rc<Cell<bool>> {
+        &self.inner.alive
+    }
+
+    pub(crate) fn shutdown_completed(&self) -> bool {
+        self.inner.shutdown_completed.get()
+    }
+
     ///Create a NUA agent.
     pub fn create(tags: &[Tag]) -> Result<Box<Nua<'a>>> {
         let root = crate::su::get_default_root()?;
@@ -50,8 +131,8 @@ impl<'a> Nua<'a> {
     }
     ///Create a NUA agent.
     pub fn create_with_root(root: &'a Root, tags: &[Tag]) -> Result<Box<Nua<'a>>> {
-        let mut nua = Box::new(Nua::_new());
-        let nua_ptr = &mut *nua as *mut Nua as *mut sys::nua_magic_t;
+        let nua = Box::new(Nua::_new(Some(root)));
+        let nua_ptr = Rc::as_ptr(&nua.inner) as *mut sys::nua_magic_t;
 
         let c_root = root.c_ptr;
         let c_callback = nua_callback_glue;
@@ -63,8 +144,8 @@ impl<'a> Nua<'a> {
         let sys_tags = Builder::_create_vec_sys_tags(&tag_items);
         let sys_tags = sys_tags.as_slice();
 
-        nua.c_ptr = Self::_create(c_root, Some(c_callback), magic, Some(sys_tags))?;
-        nua.root = Some(root);
+        let c_ptr = Self::_create(c_root, Some(c_callback), magic, Some(sys_tags))?;
+        nua.inner.c_ptr.set(c_ptr);
         Ok(nua)
     }
 
@@ -119,52 +200,258 @@ impl<'a> Nua<'a> {
         Ok(nua_sys)
     }
 
-    pub(crate) fn _on_sys_nua_event(
+    /* state kept by the stack and the handles, before the application sees the event */
+    fn _on_event_state(&self, event: &Event, status: u32, handle: Option<&Handle>) {
+        match (event, status) {
+            (Event::ReplyShutdown, x) if x >= 200 => self.inner.shutdown_completed.set(true),
+            (_, _) => {}
+        }
+        /* the call ends when the stack reports it or our BYE is answered */
+        match (event, status, handle) {
+            (Event::IncomingTerminated, _, Some(handle)) => {
+                handle.inner.terminate_completed.set(true)
+            }
+            (Event::ReplyBye, x, Some(handle)) if x >= 200 => {
+                handle.inner.terminate_completed.set(true)
+            }
+            (_, _, _) => {}
+        }
+        /* answered by the stack itself (eg: OPTIONS) */
+        if let Some(handle) = handle {
+            if handle.inner.outside_dialog.get() && status >= 200 {
+                handle.inner.answered.set(true);
+            }
+        }
+    }
+
+    pub(crate) fn _on_sys_nua_event(nua: &mut Nua, raw: RawEvent) {
+        nua._on_event_state(&raw.event, raw.status, raw.handle.as_ref());
+        if let Some(handle) = &raw.handle {
+            if !nua._on_challenge(&raw.event, raw.status, handle, || Cow::Borrowed(&raw.sip)) {
+                handle._on_reply(&raw);
+            }
+        }
+        /* closures are cloned out of their cell: they may replace themselves */
+        if let Some(cb) = raw.handle.as_ref().and_then(|handle| handle._callback()) {
+            let handle = raw.handle.unwrap();
+            cb(
+                nua, raw.event, raw.status, raw.phrase, &handle, raw.sip, raw.tags,
+            );
+            return;
+        }
+        if let Some(queue) = nua.inner.queue.borrow().as_ref() {
+            queue.borrow_mut().push_back(raw);
+            return;
+        }
+        let data_closure = nua.inner.data_closure.borrow().clone();
+        if let Some(cb) = data_closure {
+            cb(nua, EventData::from(raw));
+            return;
+        }
+        let closure = nua.inner.closure.borrow().clone();
+        if let Some(cb) = closure {
+            cb(
+                nua,
+                raw.event,
+                raw.status,
+                raw.phrase,
+                raw.handle.as_ref(),
+                raw.sip,
+                raw.tags,
+            );
+        }
+    }
+
+    /* keep a handle created by the stack */
+    pub(crate) fn _keep_adopted(&self, handle: &Handle) {
+        self._release_adopted();
+        self.inner.adopted.borrow_mut().push(handle.clone());
+    }
+
+    /* release the handles created by the stack once they are destroyed,
+    terminated or, outside of a dialog, answered */
+    pub(crate) fn _release_adopted(&self) {
+        self.inner.adopted.borrow_mut().retain(|handle| {
+            let inner = &handle.inner;
+            let done = inner.c_ptr.get().is_null()
+                || inner.terminate_completed.get()
+                || (inner.outside_dialog.get() && inner.answered.get());
+            !done
+        });
+    }
+
+    /* the event goes to the closure set by `callback_ref` */
+    pub(crate) fn _wants_sip_ref(&self, handle: Option<&Handle>) -> bool {
+        self.inner.ref_closure.borrow().is_some()
+            && handle.and_then(|handle| handle._callback()).is_none()
+    }
+
+    /* same as `_on_sys_nua_event` for the closure set by `callback_ref`, the
+    headers are only copied when a challenge is answered or a reply awaited */
+    pub(crate) fn _on_sys_nua_event_ref(
+        nua: &mut Nua,
         event: Event,
         status: u32,
         phrase: String,
-        nua_ptr: *mut Nua,
-        _handle_ptr: *mut Handle,
-        sip: Sip,
+        handle: Option<Handle>,
+        sip: SipRef<'_>,
         tags: Vec<Tag>,
     ) {
-        assert!(!nua_ptr.is_null());
-        let nua = unsafe { &mut *nua_ptr };
-        match (&event, status) {
-            (Event::ReplyShutdown, x) if x >= 200 => nua.shutdown_completed = true,
-            (_, _) => {}
+        nua._on_event_state(&event, status, handle.as_ref());
+        if let Some(handle) = &handle {
+            let restarted = nua._on_challenge(&event, status, handle, || Cow::Owned(sip.to_sip()));
+            if !restarted && status >= 200 && handle._has_waiters(&event) {
+                let raw = RawEvent {
+                    event: event.clone(),
+                    status,
+                    phrase: phrase.clone(),
+                    handle: Some(handle.clone()),
+                    sip: sip.to_sip(),
+                    tags: tags.clone(),
+                };
+                handle._on_reply(&raw);
+            }
         }
-        if let Some(cb) = &nua.closure {
-            /* FIXME: not thread safe, we create a alias to a mutable Nua */
-            let nua_for_closure = unsafe { &mut *nua_ptr };
-            cb(nua_for_closure, event, status, phrase, None, sip, tags);
+        let ref_closure = nua.inner.ref_closure.borrow().clone();
+        if let Some(cb) = ref_closure {
+            cb(nua, event, status, phrase, handle.as_ref(), sip, tags);
         }
     }
 
+    /* answer a 401/407 with the credential provider, the application still
+    receives the challenge; return true if the request was restarted */
+    fn _on_challenge<'s>(
+        &self,
+        event: &Event,
+        status: u32,
+        handle: &Handle,
+        sip: impl FnOnce() -> Cow<'s, Sip>,
+    ) -> bool {
+        let challengeable = matches!(
+            event,
+            Event::ReplyRegister
+                | Event::ReplyUnregister
+                | Event::ReplyInvite
+                | Event::ReplyBye
+                | Event::ReplyOptions
+                | Event::ReplyRefer
+                | Event::ReplyPublish
+                | Event::ReplyUnpublish
+                | Event::ReplyInfo
+                | Event::ReplyPrack
+                | Event::ReplyUpdate
+                | Event::ReplyMessage
+                | Event::ReplySubscribe
+                | Event::ReplyUnsubscribe
+                | Event::ReplyNotify
+                | Event::ReplyMethod
+        );
+        if status < 200 || !challengeable {
+            return false;
+        }
+        let attempts = &handle.inner.auth_attempts;
+        if status != 401 && status != 407 {
+            attempts.set(0);
+            return false;
+        }
+        let provider = match self.inner.credential_provider.borrow().clone() {
+            Some(provider) => provider,
+            None => return false,
+        };
+        /* credentials rejected, let the application see the failure */
+        if attempts.get() >= MAX_AUTH_ATTEMPTS {
+            attempts.set(0);
+            return false;
+        }
+        if !handle.authenticate_with(&sip(), provider.as_ref()) {
+            return false;
+        }
+        attempts.set(attempts.get() + 1);
+        true
+    }
+
     ///Root reactor object.
     pub fn root(&self) -> &su::Root {
-        match &self.root {
+        match &self.inner.root {
             Some(root) => root,
             None => crate::su::get_default_root().unwrap(),
         }
     }
 
     ///NUA event callback.
+    ///
+    ///The handle of an incoming request remains alive after the callback
+    ///returns, until [`Handle::destroy`] is called (see [`Handle`]).
     pub fn callback<F: Fn(&mut Nua, Event, u32, String, Option<&Handle>, Sip, Vec<Tag>) + 'a>(
         &mut self,
         cb: F,
     ) {
-        self.closure = Some(Box::new(cb));
+        self.inner.data_closure.replace(None);
+        self.inner.ref_closure.replace(None);
+        self.inner.queue.replace(None);
+        self.inner.closure.replace(Some(Rc::new(cb)));
+    }
+
+    ///NUA event callback with a typed payload, it replaces the callback
+    ///set by [`Nua::callback`].
+    pub fn event_callback<F: Fn(&mut Nua, EventData) + 'a>(&mut self, cb: F) {
+        self.inner.closure.replace(None);
+        self.inner.ref_closure.replace(None);
+        self.inner.queue.replace(None);
+        self.inner.data_closure.replace(Some(Rc::new(cb)));
+    }
+
+    ///NUA event callback receiving a borrowed view of the message, it
+    ///replaces the callback set by [`Nua::callback`] or [`Nua::event_callback`].
+    ///
+    ///Unlike [`Nua::callback`] the headers are not copied for each event:
+    ///[`SipRef`] decodes them when asked and [`SipRef::to_sip`] copies
+    ///them if they are needed after the callback returns.
+    pub fn callback_ref<
+        F: Fn(&mut Nua, Event, u32, String, Option<&Handle>, SipRef<'_>, Vec<Tag>) + 'a,
+    >(
+        &mut self,
+        cb: F,
+    ) {
+        self.inner.closure.replace(None);
+        self.inner.data_closure.replace(None);
+        self.inner.queue.replace(None);
+        self.inner.ref_closure.replace(Some(Rc::new(cb)));
+    }
+
+    ///Receive the events through [`Events`] instead of a callback, it
+    ///replaces the callbacks set by [`Nua::callback`] and [`Nua::event_callback`].
+    ///
+    ///All receivers returned by this method share the same queue.
+    pub fn events(&mut self) -> Events {
+        self.inner.closure.replace(None);
+        self.inner.data_closure.replace(None);
+        self.inner.ref_closure.replace(None);
+        let queue = self
+            .inner
+            .queue
+            .borrow_mut()
+            .get_or_insert_with(EventQueue::default)
+            .clone();
+        Events::_new(queue, self.root().c_ptr, self.alive().clone())
+    }
+
+    ///Answer automatically the challenges (401/407) received by the handles
+    ///of this stack with the credentials given by `provider` for each realm.
+    pub fn credential_provider<P: CredentialProvider + 'a>(&mut self, provider: P) {
+        self.inner
+            .credential_provider
+            .replace(Some(Rc::new(provider)));
     }
 
     ///Shutdown NUA stack.
     pub fn shutdown_and_wait(&self) {
-        if self.shutdown_completed {
+        if self.shutdown_completed() {
             return;
         }
 
         self.shutdown();
-        while !self.shutdown_completed {
+        while !self.shutdown_completed() {
             if self.root().step0() < 0 {
                 break;
             }
@@ -173,10 +460,10 @@ impl<'a> Nua<'a> {
 
     ///Shutdown NUA stack.
     pub fn shutdown(&self) {
-        if self.shutdown_completed {
+        if self.shutdown_completed() {
             return;
         }
-        Self::_shutdown(self.c_ptr);
+        Self::_shutdown(self.c_ptr());
     }
 
     pub(crate) fn _shutdown(nua: *mut sys::nua_s) {
@@ -186,13 +473,23 @@ impl<'a> Nua<'a> {
 
     /// Destroy the NUA stack.
     pub(crate) fn destroy(&mut self) {
-        if self.c_ptr.is_null() {
+        if self.c_ptr().is_null() {
             return;
         }
         /* before destroy we need to shutdown and wait for that shutdown */
         self.shutdown_and_wait();
-        Self::_destroy(self.c_ptr);
-        self.c_ptr = std::ptr::null_mut();
+        /* release handles kept by the closures while the stack is still alive */
+        self.inner.closure.replace(None);
+        self.inner.data_closure.replace(None);
+        self.inner.ref_closure.replace(None);
+        self.inner.credential_provider.replace(None);
+        self.inner.adopted.replace(Vec::new());
+        if let Some(queue) = self.inner.queue.replace(None) {
+            queue.borrow_mut().clear();
+        }
+        let c_ptr = self.inner.c_ptr.replace(std::ptr::null_mut());
+        Self::_destroy(c_ptr);
+        self.inner.alive.set(false);
     }
 
     pub(crate) fn _destroy(nua: *mut sys::nua_s) {
@@ -207,12 +504,12 @@ impl<'a> Nua<'a> {
 
     ///Run event and message loop.
     pub fn run(&self) {
-        self.root.unwrap().run();
+        self.root().run();
     }
 
     ///Terminate event loop.
     pub fn r#break(&self) {
-        self.root.unwrap().r#break();
+        self.root().r#break();
     }
 
     ///Terminate event loop.
@@ -228,7 +525,9 @@ impl<'a> Nua<'a> {
 
 impl<'a> Drop for Nua<'a> {
     fn drop(&mut self) {
-        self.destroy()
+        if self.owner {
+            self.destroy()
+        }
     }
 }
 
@@ -246,6 +545,19 @@ fn tagi_t_list_as_string(lst: *const sys::tagi_t) -> String {
     return output;
 }
 
+/* the stack creates a handle for `event` without a dialog (eg: MESSAGE) */
+fn is_outside_dialog(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::IncomingMessage
+            | Event::IncomingOptions
+            | Event::IncomingPublish
+            | Event::IncomingRegister
+            | Event::IncomingInfo
+            | Event::IncomingMethod
+    )
+}
+
 /// Called from C code, it will convert C types to Rust types and call Rust function with these types
 extern "C" fn nua_callback_glue(
     _event: sys::nua_event_t,
@@ -278,26 +590,28 @@ extern "C" fn nua_callback_glue(
 
         let sys_nua = _nua; /* ignored */
 
-        let nua: *mut Nua = _magic as *mut Nua;
+        /* the owner is alive while the stack runs (it is destroyed on drop) */
+        let mut nua = unsafe { Nua::_from_magic(_magic) };
 
         /* sanity check for Nua */
-        {
-            assert!(!nua.is_null());
-            let nua: &Nua = unsafe { &*nua };
-            assert_eq!(sys_nua, nua.c_ptr);
-        }
+        assert_eq!(sys_nua, nua.c_ptr());
 
-        let sys_handle = _nh; /* ignored */
+        let sys_handle = _nh;
 
-        let handle: *mut Handle = _hmagic as *mut Handle;
-        // let handle_struct: Option<&Handle>;
-        /* sanity check for Handle */
-        if !handle.is_null() {
+        let handle: Option<Handle> = if !_hmagic.is_null() {
             /* reply to an owned handle function (outgoing sip message) */
-            let handle: &Handle = unsafe { &*handle };
-            assert_eq!(sys_handle, handle.c_ptr);
-            // handle_struct = Some(handle_struct_temp);
-        }
+            let handle = unsafe { Handle::_from_hmagic(_hmagic) };
+            /* sanity check for Handle */
+            assert_eq!(sys_handle, handle.c_ptr());
+            Some(handle)
+        } else if !sys_handle.is_null() {
+            /* incoming request, the handle was created by the stack */
+            let handle = Handle::_adopt(&nua, sys_handle);
+            handle.inner.outside_dialog.set(is_outside_dialog(&event));
+            Some(handle)
+        } else {
+            None
+        };
 
         // println!(
         //     "-[tags start]-\n{}\n-[tags end]-",
@@ -306,6 +620,14 @@ extern "C" fn nua_callback_glue(
         let tags = Builder::_from_sys(_tags).collect();
         // dbg!(&tags);
 
+        if nua._wants_sip_ref(handle.as_ref()) {
+            /* the message is borrowed for the duration of the callback */
+            let sip = unsafe { SipRef::_from_sys(_sip) };
+            Nua::_on_sys_nua_event_ref(&mut nua, event, status, phrase, handle, sip, tags);
+            nua._release_adopted();
+            return;
+        }
+
         // if !_tags.is_null() {
         // loop {
         // let t = unsafe { *_tags.offset(0) };
@@ -323,7 +645,16 @@ extern "C" fn nua_callback_glue(
         let sip = Sip::_from_sys(_sip);
 
         // println!("------ [nua_callback_glue] ------");
-        Nua::_on_sys_nua_event(event, status, phrase, nua, handle, sip, tags);
+        let raw = RawEvent {
+            event,
+            status,
+            phrase,
+            handle,
+            sip,
+            tags,
+        };
+        Nua::_on_sys_nua_event(&mut nua, raw);
+        nua._release_adopted();
     }) {
         // Code here must be panic-free.
         let error = format!("PANIC!! while calling a callback from C: {:?}\n\0", e);
@@ -431,6 +762,22 @@ mod tests {
         let mut nua = Nua::create_full(&root, cb, &tags).unwrap();
     }
 
+    #[test]
+    #[adorn(wrap)]
+    #[serial]
+    fn create_handle_and_clone_it() {
+        let tags = TagBuilder::default().collect();
+        let nua = Nua::create(&tags).unwrap();
+
+        let handle = Handle::create(&nua, &tags).unwrap();
+        let other = handle.clone();
+        assert_eq!(handle.c_ptr(), other.c_ptr());
+
+        /* the operation remains alive while any clone exists */
+        drop(handle);
+        assert!(!other.c_ptr().is_null());
+    }
+
     #[test]
     #[adorn(wrap)]
     #[serial]
@@ -468,4 +815,251 @@ mod tests {
 
         assert!(Nua::create_with_root(&root, &tags).is_err());
     }
+
+    /* no C calls in the ownership tests: a stack with a null c_ptr */
+    fn raw(event: NuaEvent, status: u32) -> RawEvent {
+        RawEvent {
+            event,
+            status,
+            phrase: String::new(),
+            handle: None,
+            sip: Sip::default(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ownership_from_magic_does_not_own() {
+        let nua = Nua::_new(None);
+        let magic = Rc::as_ptr(&nua.inner) as *mut sys::nua_magic_t;
+        {
+            let borrowed = unsafe { Nua::_from_magic(magic) };
+            assert!(!borrowed.owner);
+            assert_eq!(Rc::strong_count(&nua.inner), 2);
+        }
+        assert_eq!(Rc::strong_count(&nua.inner), 1);
+        assert!(nua.alive().get());
+    }
+
+    #[test]
+    fn ownership_dispatch_to_closure() {
+        let mut nua = Nua::_new(None);
+        let count = Rc::new(Cell::new(0));
+        let c = count.clone();
+        nua.callback(move |_: &mut Nua, event, status, _, handle, _, _| {
+            assert_eq!(event, NuaEvent::ReplyShutdown);
+            assert_eq!(status, 200);
+            assert!(handle.is_none());
+            c.set(c.get() + 1);
+        });
+        let magic = Rc::as_ptr(&nua.inner) as *mut sys::nua_magic_t;
+        let mut borrowed = unsafe { Nua::_from_magic(magic) };
+        Nua::_on_sys_nua_event(&mut borrowed, raw(NuaEvent::ReplyShutdown, 200));
+        assert_eq!(count.get(), 1);
+        assert!(nua.shutdown_completed());
+    }
+
+    #[test]
+    fn ownership_dispatch_to_callback_ref() {
+        let mut nua = Nua::_new(None);
+        let count = Rc::new(Cell::new(0));
+        let c = count.clone();
+        nua.callback(|_: &mut Nua, _, _, _, _, _, _| panic!("replaced by callback_ref"));
+        nua.callback_ref(move |_: &mut Nua, event, status, _, handle, sip, _| {
+            assert_eq!(event, NuaEvent::ReplyShutdown);
+            assert_eq!(status, 200);
+            assert!(handle.is_none());
+            assert!(sip.call_id().id().is_empty());
+            c.set(c.get() + 1);
+        });
+        assert!(nua._wants_sip_ref(None));
+        let magic = Rc::as_ptr(&nua.inner) as *mut sys::nua_magic_t;
+        let mut borrowed = unsafe { Nua::_from_magic(magic) };
+        let sip = unsafe { SipRef::_from_sys(std::ptr::null()) };
+        Nua::_on_sys_nua_event_ref(
+            &mut borrowed,
+            NuaEvent::ReplyShutdown,
+            200,
+            String::new(),
+            None,
+            sip,
+            Vec::new(),
+        );
+        assert_eq!(count.get(), 1);
+        assert!(nua.shutdown_completed());
+
+        nua.callback(|_: &mut Nua, _, _, _, _, _, _| {});
+        assert!(!nua._wants_sip_ref(None));
+    }
+
+    #[test]
+    fn ownership_adopted_handles_are_kept() {
+        let nua = Nua::_new(None);
+        /* a stack already destroyed: no C call when the handles are released */
+        let handle = Handle::_from_parts(Rc::new(Cell::new(false)), std::ptr::null_mut());
+        handle
+            .inner
+            .c_ptr
+            .set(std::ptr::NonNull::dangling().as_ptr());
+        nua._keep_adopted(&handle);
+        let inner = Rc::downgrade(&handle.inner);
+        drop(handle);
+        assert!(inner.upgrade().is_some());
+
+        /* released once destroyed */
+        inner.upgrade().unwrap().destroy();
+        let other = Handle::_from_parts(Rc::new(Cell::new(false)), std::ptr::null_mut());
+        nua._keep_adopted(&other);
+        assert!(inner.upgrade().is_none());
+        assert_eq!(nua.inner.adopted.borrow().len(), 1);
+    }
+
+    #[test]
+    fn ownership_adopted_handles_are_released() {
+        let nua = Nua::_new(None);
+        let adopt = |outside_dialog: bool| {
+            let handle = Handle::_from_parts(Rc::new(Cell::new(false)), std::ptr::null_mut());
+            handle
+                .inner
+                .c_ptr
+                .set(std::ptr::NonNull::dangling().as_ptr());
+            handle.inner.outside_dialog.set(outside_dialog);
+            nua._keep_adopted(&handle);
+            handle
+        };
+        let message = adopt(true);
+        let options = adopt(true);
+        let invite = adopt(false);
+        assert_eq!(nua.inner.adopted.borrow().len(), 3);
+
+        /* answered by the application */
+        message.inner.answered.set(true);
+        /* answered by the stack */
+        nua._on_event_state(&Event::IncomingOptions, 200, Some(&options));
+        /* a final response does not end a dialog */
+        nua._on_event_state(&Event::IncomingInvite, 200, Some(&invite));
+        nua._release_adopted();
+        assert_eq!(*nua.inner.adopted.borrow(), vec![invite.clone()]);
+
+        nua._on_event_state(&Event::IncomingTerminated, 200, Some(&invite));
+        nua._release_adopted();
+        assert!(nua.inner.adopted.borrow().is_empty());
+    }
+
+    #[test]
+    fn answered_bye_terminates_the_call() {
+        let nua = Nua::_new(None);
+        let handle = Handle::_from_parts(Rc::new(Cell::new(false)), std::ptr::null_mut());
+        nua._on_event_state(&Event::ReplyBye, 100, Some(&handle));
+        assert!(!handle.inner.terminate_completed.get());
+        nua._on_event_state(&Event::ReplyBye, 481, Some(&handle));
+        assert!(handle.inner.terminate_completed.get());
+    }
+
+    #[test]
+    #[adorn(wrap)]
+    #[serial]
+    fn adopted_handles_of_messages_are_released() {
+        const MESSAGES: usize = 10;
+
+        let nua_b_url = "sip:127.0.0.1:5081";
+        let mut nua_a = {
+            let tags = TagBuilder::default()
+                .nutag_url("sip:127.0.0.1:5080")
+                .collect();
+            Nua::create(&tags).unwrap()
+        };
+        let mut nua_b = {
+            let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+            Nua::create(&tags).unwrap()
+        };
+
+        /* largest number of handles kept by B, while it receives a MESSAGE */
+        let b_kept = Rc::new(Cell::new(0));
+        let a_replies = Rc::new(Cell::new(0));
+
+        {
+            let b_kept = b_kept.clone();
+            nua_b.callback(move |nua: &mut Nua, event, _, _, _, _, _| {
+                if let NuaEvent::IncomingMessage = event {
+                    let kept = nua.inner.adopted.borrow().len();
+                    b_kept.set(b_kept.get().max(kept));
+                }
+            });
+        }
+        {
+            let a_replies = a_replies.clone();
+            nua_a.callback(move |nua: &mut Nua, event, status, _, _, _, _| {
+                if let (NuaEvent::ReplyMessage, 200..=699) = (event, status) {
+                    a_replies.set(a_replies.get() + 1);
+                    if a_replies.get() == MESSAGES {
+                        nua.root().break_();
+                    }
+                }
+            });
+        }
+
+        let handles: Vec<Handle> = (0..MESSAGES)
+            .map(|i| {
+                let tags = TagBuilder::default()
+                    .siptag_to_str(nua_b_url)
+                    .nutag_url(nua_b_url)
+                    .collect();
+                let handle = Handle::create(&nua_a, &tags).unwrap();
+                let tags = TagBuilder::default()
+                    .siptag_content_type_str("text/plain")
+                    .siptag_payload_str(&format!("message {}", i))
+                    .collect();
+                handle.message(&tags);
+                handle
+            })
+            .collect();
+
+        Root::get_default_root().unwrap().run();
+
+        assert_eq!(a_replies.get(), MESSAGES);
+        assert_eq!(b_kept.get(), 1);
+        assert!(nua_b.inner.adopted.borrow().is_empty());
+        drop(handles);
+    }
+
+    #[test]
+    fn ownership_callback_replaced_from_callback() {
+        let mut nua = Nua::_new(None);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let s = seen.clone();
+        nua.callback(move |nua: &mut Nua, event, _, _, _, _, _| {
+            s.borrow_mut().push(("first", event));
+            let s = s.clone();
+            nua.callback(move |_: &mut Nua, event, _, _, _, _, _| {
+                s.borrow_mut().push(("second", event));
+            });
+        });
+        let magic = Rc::as_ptr(&nua.inner) as *mut sys::nua_magic_t;
+        let mut borrowed = unsafe { Nua::_from_magic(magic) };
+        Nua::_on_sys_nua_event(&mut borrowed, raw(NuaEvent::ReplyMessage, 200));
+        Nua::_on_sys_nua_event(&mut borrowed, raw(NuaEvent::ReplyMessage, 200));
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                ("first", NuaEvent::ReplyMessage),
+                ("second", NuaEvent::ReplyMessage)
+            ]
+        );
+    }
+
+    #[test]
+    fn ownership_dispatch_to_events() {
+        let mut nua = Nua::_new(None);
+        let queue = EventQueue::default();
+        nua.inner.queue.replace(Some(queue.clone()));
+        let magic = Rc::as_ptr(&nua.inner) as *mut sys::nua_magic_t;
+        let mut borrowed = unsafe { Nua::_from_magic(magic) };
+        Nua::_on_sys_nua_event(&mut borrowed, raw(NuaEvent::IncomingMessage, 100));
+        assert_eq!(queue.borrow().len(), 1);
+        drop(borrowed);
+        drop(nua);
+        /* the owner drops its queue with the stack */
+        assert_eq!(Rc::strong_count(&queue), 1);
+    }
 }
@@ -0,0 +1,243 @@
+use crate::error::Error;
+use crate::nua::Event;
+use crate::nua::Handle;
+use crate::nua::Nua;
+use crate::result::Result;
+use crate::su::Root;
+use crate::sys;
+use crate::tag::builder::Builder;
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Result of an OPTIONS request sent by a [`Qualifier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualifyResult {
+    uri: String,
+    status: u32,
+    phrase: String,
+    rtt: Duration,
+    /* generated by the stack (no Via), not received from the URI */
+    local: bool,
+}
+
+impl QualifyResult {
+    pub fn uri(&self) -> &String {
+        &self.uri
+    }
+
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+
+    pub fn phrase(&self) -> &String {
+        &self.phrase
+    }
+
+    /// Round-trip time between the OPTIONS request and its final response.
+    pub fn rtt(&self) -> Duration {
+        self.rtt
+    }
+
+    /// Any final response received from the URI means it is reachable, a
+    /// 408 (timeout) or 503 (transport error) generated by the stack means
+    /// it is not.
+    pub fn reachable(&self) -> bool {
+        !(self.local && matches!(self.status, 408 | 503))
+    }
+}
+
+struct Target {
+    uri: String,
+    handle: Handle,
+    /* when the pending OPTIONS was sent */
+    sent_at: Cell<Option<Instant>>,
+}
+
+/* shared by the qualifier, the NUA callback and the timer callback */
+struct QualifierState {
+    targets: Vec<Target>,
+}
+
+impl QualifierState {
+    fn qualify(&self) {
+        for target in &self.targets {
+            /* previous OPTIONS not answered yet */
+            if target.sent_at.get().is_some() {
+                continue;
+            }
+            target.sent_at.set(Some(Instant::now()));
+            target.handle.options(&[]);
+        }
+    }
+
+    fn on_reply(
+        &self,
+        handle: &Handle,
+        status: u32,
+        phrase: String,
+        local: bool,
+    ) -> Option<QualifyResult> {
+        let target = self.targets.iter().find(|t| t.handle == *handle)?;
+        let sent_at = target.sent_at.take()?;
+        Some(QualifyResult {
+            uri: target.uri.clone(),
+            status,
+            phrase,
+            rtt: sent_at.elapsed(),
+            local,
+        })
+    }
+}
+
+/// Check periodically the reachability of a list of URIs using SIP OPTIONS.
+///
+/// The qualifier sends its requests from handles of the given NUA stack,
+/// when its root runs (see [`Root::run`] and [`Root::step`]). The replies
+/// go to the qualifier, not to the callback of the stack.
+pub struct Qualifier<'a> {
+    state: Rc<QualifierState>,
+    timer: *mut sys::su_timer_t,
+    _root: PhantomData<&'a Root>,
+}
+
+impl<'a> std::fmt::Debug for Qualifier<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let uris: Vec<&String> = self.state.targets.iter().map(|t| &t.uri).collect();
+        f.debug_struct("Qualifier")
+            .field("uris", &uris)
+            .field("timer", &self.timer)
+            .finish()
+    }
+}
+
+impl<'a> Qualifier<'a> {
+    /// Create a qualifier that sends an OPTIONS request to each URI from
+    /// `nua` now and then every `interval`; `on_result` is called for each
+    /// final response.
+    pub fn create<F: Fn(&QualifyResult) + 'static>(
+        nua: &'a Nua,
+        uris: &[&str],
+        interval: Duration,
+        on_result: F,
+    ) -> Result<Qualifier<'a>> {
+        let root = nua.root();
+
+        let mut targets = Vec::with_capacity(uris.len());
+        for uri in uris {
+            let tags = Builder::default()
+                .nutag_url(uri)
+                .siptag_to_str(uri)
+                .collect();
+            targets.push(Target {
+                uri: uri.to_string(),
+                handle: Handle::create(nua, &tags)?,
+                sent_at: Cell::new(None),
+            });
+        }
+        let state = Rc::new(QualifierState { targets });
+
+        let on_result: Rc<dyn Fn(&QualifyResult)> = Rc::new(on_result);
+        for target in &state.targets {
+            /* weak: the handles (and their closures) belong to the state */
+            let state = Rc::downgrade(&state);
+            let on_result = on_result.clone();
+            target.handle.callback(
+                move |_nua: &mut Nua, event: Event, status: u32, phrase, handle, sip, _tags| {
+                    if status < 200 {
+                        return;
+                    }
+                    if let (Event::ReplyOptions, Some(state)) = (event, state.upgrade()) {
+                        let local = sip.via().is_empty();
+                        if let Some(result) = state.on_reply(handle, status, phrase, local) {
+                            on_result(&result);
+                        }
+                    }
+                },
+            );
+        }
+
+        let timer = unsafe {
+            let task = sys::su_root_task(root.c_ptr);
+            sys::su_timer_create(task, interval.as_millis() as sys::su_duration_t)
+        };
+        if timer.is_null() {
+            return Err(Error::CreateTimerError);
+        }
+        let arg = Rc::as_ptr(&state) as *mut sys::su_timer_arg_t;
+        if unsafe { sys::su_timer_run(timer, Some(qualifier_timer_glue), arg) } < 0 {
+            unsafe { sys::su_timer_destroy(timer) };
+            return Err(Error::CreateTimerError);
+        }
+
+        state.qualify();
+
+        Ok(Qualifier {
+            state,
+            timer,
+            _root: PhantomData,
+        })
+    }
+
+    /// Send an OPTIONS request to each URI without a pending request now.
+    pub fn qualify(&self) {
+        self.state.qualify();
+    }
+}
+
+impl<'a> Drop for Qualifier<'a> {
+    fn drop(&mut self) {
+        /* stop the timer before its argument (the state) is released */
+        unsafe { sys::su_timer_destroy(self.timer) };
+        self.timer = std::ptr::null_mut();
+    }
+}
+
+/// Called from C code when the qualify interval elapses.
+extern "C" fn qualifier_timer_glue(
+    _magic: *mut sys::su_root_magic_t,
+    _timer: *mut sys::su_timer_t,
+    arg: *mut sys::su_timer_arg_t,
+) {
+    if let Err(e) = std::panic::catch_unwind(|| {
+        assert!(!arg.is_null());
+        let state: &QualifierState = unsafe { &*(arg as *const QualifierState) };
+        state.qualify();
+    }) {
+        // Code here must be panic-free.
+        let error = format!("PANIC!! while calling a timer callback from C: {:?}\n\0", e);
+        eprint!("{}", &error);
+        // Abort is safe because it doesn't unwind.
+        std::process::abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status: u32, local: bool) -> QualifyResult {
+        QualifyResult {
+            uri: "sip:127.0.0.1".to_string(),
+            status,
+            phrase: String::new(),
+            rtt: Duration::default(),
+            local,
+        }
+    }
+
+    #[test]
+    fn test_reachable() {
+        assert!(result(200, false).reachable());
+        assert!(result(404, false).reachable());
+        /* answered by the URI (or a proxy on its path) */
+        assert!(result(408, false).reachable());
+        assert!(result(503, false).reachable());
+        /* generated by the stack */
+        assert!(!result(408, true).reachable());
+        assert!(!result(503, true).reachable());
+    }
+}
@@ -1,13 +1,25 @@
+use crate::dtmf::Dtmf;
+use crate::error::Error;
+use crate::result::Result;
 use crate::sys;
 // use std::convert::From;
 use std::convert::Into;
 use std::fmt;
 
+use std::borrow::Cow;
 use std::ffi::CStr;
+use std::ffi::CString;
+use std::marker::PhantomData;
 
 type SipSubject = MsgGeneric;
+type SipETag = MsgGeneric;
+type SipIfMatch = MsgGeneric;
 type SipContentType = MsgContentType;
 type SipPayload = MsgPayload;
+type SipUserAgent = MsgGeneric;
+type SipAllow = MsgList;
+type SipSupported = MsgList;
+type SipRequire = MsgList;
 
 /// Convert an url to a String.
 fn url_as_string(sys_url_ptr: *const sys::url_t) -> String {
@@ -23,8 +35,34 @@ fn url_as_string(sys_url_ptr: *const sys::url_t) -> String {
     String::from_utf8_lossy(&buf[..len]).to_string()
 }
 
+/// Convert a nullable c string to an Option<String>.
+fn cstr_as_option_string(ptr: *const std::os::raw::c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(unsafe { CStr::from_ptr(ptr).to_string_lossy().into_owned() })
+}
+
+/// Convert a NULL terminated list of parameters to a Vec<String>.
+fn msg_params_as_vec(params: *const sys::msg_param_t) -> Vec<String> {
+    let mut v = Vec::new();
+    if params.is_null() {
+        return v;
+    }
+    let mut i = 0;
+    loop {
+        let param = unsafe { *params.offset(i) };
+        if param.is_null() {
+            break;
+        }
+        v.push(cstr_as_option_string(param).unwrap());
+        i += 1;
+    }
+    v
+}
+
 /******************/
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct MsgPayload {
     exists: bool,
     data: Vec<u8>,
@@ -69,7 +107,7 @@ impl Into<String> for &MsgPayload {
 }
 
 /******************/
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct MsgContentType {
     exists: bool,
     r#type: String,
@@ -116,7 +154,7 @@ impl Into<String> for &MsgContentType {
 }
 
 /******************/
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct MsgGeneric {
     exists: bool,
     string: String,
@@ -156,7 +194,7 @@ impl Into<String> for &MsgGeneric {
 }
 
 /**********************************/
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct SipAddr {
     exists: bool,
     display: String,
@@ -188,6 +226,26 @@ impl SipAddr {
         addr
     }
 
+    pub(crate) fn _from_sys_refer_to(sys_refer_to: *const sys::sip_refer_to_t) -> Self {
+        let mut addr = Self::default();
+        if sys_refer_to.is_null() {
+            return addr;
+        }
+        let sys_refer_to = unsafe { *sys_refer_to };
+
+        if let Some(display) = cstr_as_option_string(sys_refer_to.r_display) {
+            addr.display = display;
+        }
+
+        let sys_url_ptr: *const sys::url_t = &sys_refer_to.r_url[0];
+
+        addr.url = url_as_string(sys_url_ptr);
+
+        addr.exists = true;
+
+        addr
+    }
+
     pub fn display(&self) -> &String {
         &self.display
     }
@@ -214,54 +272,2377 @@ impl Into<String> for &SipAddr {
 }
 
 /**********************************/
-#[derive(Default, Debug)]
-pub struct Sip {
+#[derive(Default, Debug, Clone)]
+pub struct SipContact {
+    display: String,
+    url: String,
+    params: Vec<String>,
+    q: Option<String>,
+    expires: Option<u32>,
+}
+
+impl SipContact {
+    pub(crate) fn _from_sys(sys_contact: *const sys::sip_contact_t) -> Vec<Self> {
+        let mut list = Vec::new();
+        let mut sys_contact = sys_contact;
+        while !sys_contact.is_null() {
+            let m = unsafe { *sys_contact };
+            let sys_url_ptr: *const sys::url_t = &m.m_url[0];
+            list.push(Self {
+                display: cstr_as_option_string(m.m_display).unwrap_or_default(),
+                url: url_as_string(sys_url_ptr),
+                params: msg_params_as_vec(m.m_params),
+                q: cstr_as_option_string(m.m_q),
+                expires: cstr_as_option_string(m.m_expires).and_then(|s| s.parse().ok()),
+            });
+            sys_contact = m.m_next;
+        }
+        list
+    }
+
+    pub fn display(&self) -> &String {
+        &self.display
+    }
+    pub fn url(&self) -> &String {
+        &self.url
+    }
+    /// Raw parameters, eg: `expires=3600`.
+    pub fn params(&self) -> &Vec<String> {
+        &self.params
+    }
+    pub fn q(&self) -> Option<&String> {
+        self.q.as_ref()
+    }
+    /// Value of the `expires` parameter, in seconds.
+    pub fn expires(&self) -> Option<u32> {
+        self.expires
+    }
+}
+
+impl fmt::Display for SipContact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipContact {
+    fn into(self) -> String {
+        let mut r = if !self.display.is_empty() {
+            format!("{} <{}>", self.display, self.url)
+        } else {
+            format!("<{}>", self.url)
+        };
+        for param in &self.params {
+            r.push(';');
+            r.push_str(param);
+        }
+        r
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipEvent {
     exists: bool,
-    from: SipAddr,
-    to: SipAddr,
-    subject: SipSubject,
-    content_type: SipContentType,
-    payload: SipPayload,
+    r#type: String,
+    id: Option<String>,
 }
-impl Sip {
-    pub(crate) fn _from_sys(sys_sip: *const sys::sip_t) -> Self {
-        let mut sip = Self::default();
-        if sys_sip.is_null() {
-            return sip;
+
+impl SipEvent {
+    pub(crate) fn _from_sys(sys_event: *const sys::sip_event_t) -> Self {
+        let mut event = Self::default();
+        if sys_event.is_null() {
+            return event;
         }
+        let sys_event = unsafe { *sys_event };
 
-        let sys_sip = unsafe { *sys_sip };
+        assert!(!sys_event.o_type.is_null());
+        event.r#type = cstr_as_option_string(sys_event.o_type).unwrap();
+        event.id = cstr_as_option_string(sys_event.o_id);
 
-        sip.from = SipAddr::_from_sys(sys_sip.sip_from);
-        sip.to = SipAddr::_from_sys(sys_sip.sip_to);
+        event.exists = true;
 
-        sip.subject = SipSubject::_from_sys(sys_sip.sip_subject);
+        event
+    }
 
-        sip.content_type = SipContentType::_from_sys(sys_sip.sip_content_type);
+    pub fn r#type(&self) -> &String {
+        &self.r#type
+    }
+    pub fn id(&self) -> Option<&String> {
+        self.id.as_ref()
+    }
+}
 
-        sip.payload = SipPayload::_from_sys(sys_sip.sip_payload);
+impl fmt::Display for SipEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
 
-        sip.exists = true;
-        sip
+impl Into<String> for &SipEvent {
+    fn into(self) -> String {
+        match &self.id {
+            Some(id) => format!("{};id={}", self.r#type, id),
+            None => self.r#type.clone(),
+        }
     }
+}
 
-    pub fn from(&self) -> &SipAddr {
-        &self.from
+/**********************************/
+/// Value of `substate-value` in a Subscription-State header (RFC 6665).
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionState {
+    Active,
+    Pending,
+    Terminated,
+    Other(String),
+}
+
+impl Default for SubscriptionState {
+    fn default() -> Self {
+        SubscriptionState::Other(String::new())
     }
+}
 
-    pub fn to(&self) -> &SipAddr {
-        &self.to
+impl SubscriptionState {
+    pub(crate) fn _from_sys(substate: i32) -> Self {
+        match substate {
+            sys::nua_substate_nua_substate_pending => SubscriptionState::Pending,
+            sys::nua_substate_nua_substate_active => SubscriptionState::Active,
+            sys::nua_substate_nua_substate_terminated => SubscriptionState::Terminated,
+            sys::nua_substate_nua_substate_embryonic => {
+                SubscriptionState::Other("embryonic".to_string())
+            }
+            _ => SubscriptionState::Other("extended".to_string()),
+        }
     }
 
-    pub fn subject(&self) -> &SipSubject {
-        &self.subject
+    pub(crate) fn _to_sys(&self) -> i32 {
+        match self {
+            SubscriptionState::Pending => sys::nua_substate_nua_substate_pending,
+            SubscriptionState::Active => sys::nua_substate_nua_substate_active,
+            SubscriptionState::Terminated => sys::nua_substate_nua_substate_terminated,
+            SubscriptionState::Other(_) => sys::nua_substate_nua_substate_extended,
+        }
     }
+}
 
-    pub fn content_type(&self) -> &SipContentType {
-        &self.content_type
+impl From<&str> for SubscriptionState {
+    fn from(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "active" => SubscriptionState::Active,
+            "pending" => SubscriptionState::Pending,
+            "terminated" => SubscriptionState::Terminated,
+            _ => SubscriptionState::Other(s.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SubscriptionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubscriptionState::Active => write!(f, "active"),
+            SubscriptionState::Pending => write!(f, "pending"),
+            SubscriptionState::Terminated => write!(f, "terminated"),
+            SubscriptionState::Other(s) => write!(f, "{}", s),
+        }
     }
+}
 
-    pub fn payload(&self) -> &SipPayload {
-        &self.payload
+#[derive(Default, Debug, Clone)]
+pub struct SipSubscriptionState {
+    exists: bool,
+    state: SubscriptionState,
+    reason: Option<String>,
+    expires: Option<u32>,
+    retry_after: Option<u32>,
+}
+
+impl SipSubscriptionState {
+    pub(crate) fn _from_sys(sys_ss: *const sys::sip_subscription_state_t) -> Self {
+        let mut ss = Self::default();
+        if sys_ss.is_null() {
+            return ss;
+        }
+        let sys_ss = unsafe { *sys_ss };
+
+        assert!(!sys_ss.ss_substate.is_null());
+        ss.state =
+            SubscriptionState::from(cstr_as_option_string(sys_ss.ss_substate).unwrap().as_str());
+        ss.reason = cstr_as_option_string(sys_ss.ss_reason);
+        ss.expires = cstr_as_option_string(sys_ss.ss_expires).and_then(|s| s.parse().ok());
+        ss.retry_after = cstr_as_option_string(sys_ss.ss_retry_after).and_then(|s| s.parse().ok());
+
+        ss.exists = true;
+
+        ss
+    }
+
+    pub fn state(&self) -> &SubscriptionState {
+        &self.state
+    }
+    pub fn reason(&self) -> Option<&String> {
+        self.reason.as_ref()
+    }
+    pub fn expires(&self) -> Option<u32> {
+        self.expires
+    }
+    pub fn retry_after(&self) -> Option<u32> {
+        self.retry_after
+    }
+}
+
+impl fmt::Display for SipSubscriptionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipSubscriptionState {
+    fn into(self) -> String {
+        let mut r = self.state.to_string();
+        if let Some(reason) = &self.reason {
+            r.push_str(&format!(";reason={}", reason));
+        }
+        if let Some(expires) = self.expires {
+            r.push_str(&format!(";expires={}", expires));
+        }
+        if let Some(retry_after) = self.retry_after {
+            r.push_str(&format!(";retry-after={}", retry_after));
+        }
+        r
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipReplaces {
+    exists: bool,
+    call_id: String,
+    from_tag: String,
+    to_tag: String,
+    early_only: bool,
+}
+
+impl SipReplaces {
+    pub(crate) fn _from_sys(sys_rp: *const sys::sip_replaces_t) -> Self {
+        let mut rp = Self::default();
+        if sys_rp.is_null() {
+            return rp;
+        }
+        let sys_rp = unsafe { *sys_rp };
+
+        assert!(!sys_rp.rp_call_id.is_null());
+        rp.call_id = cstr_as_option_string(sys_rp.rp_call_id).unwrap();
+        rp.from_tag = cstr_as_option_string(sys_rp.rp_from_tag).unwrap_or_default();
+        rp.to_tag = cstr_as_option_string(sys_rp.rp_to_tag).unwrap_or_default();
+        rp.early_only = sys_rp.rp_early_only() != 0;
+
+        rp.exists = true;
+
+        rp
+    }
+
+    pub fn call_id(&self) -> &String {
+        &self.call_id
+    }
+    pub fn from_tag(&self) -> &String {
+        &self.from_tag
+    }
+    pub fn to_tag(&self) -> &String {
+        &self.to_tag
+    }
+    pub fn early_only(&self) -> bool {
+        self.early_only
+    }
+}
+
+impl fmt::Display for SipReplaces {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipReplaces {
+    fn into(self) -> String {
+        if !self.exists {
+            return String::new();
+        }
+        let mut r = format!(
+            "{};from-tag={};to-tag={}",
+            self.call_id, self.from_tag, self.to_tag
+        );
+        if self.early_only {
+            r.push_str(";early-only");
+        }
+        r
+    }
+}
+
+/**********************************/
+/// A challenge (WWW-Authenticate, Proxy-Authenticate) or the credentials
+/// answering it (Authorization, Proxy-Authorization).
+#[derive(Default, Debug, Clone)]
+pub struct SipAuth {
+    scheme: String,
+    params: Vec<String>,
+}
+
+impl SipAuth {
+    pub(crate) fn _from_sys(sys_au: *const sys::msg_auth_t) -> Vec<Self> {
+        let mut list = Vec::new();
+        let mut sys_au = sys_au;
+        while !sys_au.is_null() {
+            let au = unsafe { *sys_au };
+            assert!(!au.au_scheme.is_null());
+            list.push(Self {
+                scheme: cstr_as_option_string(au.au_scheme).unwrap(),
+                params: msg_params_as_vec(au.au_params),
+            });
+            sys_au = au.au_next;
+        }
+        list
+    }
+
+    /// Authentication scheme, eg: `Digest`.
+    pub fn scheme(&self) -> &String {
+        &self.scheme
+    }
+    /// Raw parameters, eg: `realm="example.com"`.
+    pub fn params(&self) -> &Vec<String> {
+        &self.params
+    }
+    /// Value of the parameter `name` (case insensitive), without quotes.
+    pub fn param(&self, name: &str) -> Option<String> {
+        self.params.iter().find_map(|param| {
+            let mut kv = param.splitn(2, '=');
+            let key = kv.next()?.trim();
+            if !key.eq_ignore_ascii_case(name) {
+                return None;
+            }
+            let value = kv.next().unwrap_or("").trim();
+            Some(value.trim_matches('"').to_string())
+        })
+    }
+    pub fn realm(&self) -> Option<String> {
+        self.param("realm")
+    }
+    pub fn username(&self) -> Option<String> {
+        self.param("username")
+    }
+}
+
+impl fmt::Display for SipAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipAuth {
+    fn into(self) -> String {
+        if self.params.is_empty() {
+            return self.scheme.clone();
+        }
+        format!("{} {}", self.scheme, self.params.join(", "))
+    }
+}
+
+/**********************************/
+/// Status line of a `message/sipfrag` body (RFC 3420), as sent in the NOTIFY
+/// requests that report the progress of a REFER (RFC 3515).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SipFrag {
+    status: u32,
+    phrase: String,
+}
+
+impl SipFrag {
+    /// Parse the status line of a sipfrag, eg: `SIP/2.0 180 Ringing`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let line = s.lines().next()?;
+        let mut parts = line.trim().splitn(3, ' ');
+        if !parts.next()?.starts_with("SIP/") {
+            return None;
+        }
+        let status = parts.next()?.parse().ok()?;
+        let phrase = parts.next().unwrap_or("").to_string();
+        Some(Self { status, phrase })
+    }
+
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+    pub fn phrase(&self) -> &String {
+        &self.phrase
+    }
+}
+
+impl fmt::Display for SipFrag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SIP/2.0 {} {}", self.status, self.phrase)
+    }
+}
+
+/******************/
+/// A header with a comma separated list of tokens (Allow, Supported,
+/// Require).
+#[derive(Default, Debug, Clone)]
+pub struct MsgList {
+    exists: bool,
+    items: Vec<String>,
+}
+
+impl MsgList {
+    pub(crate) fn _from_sys(sys_msg: *const sys::msg_list_t) -> Self {
+        let mut msg = Self::default();
+        let mut sys_msg = sys_msg;
+        /* the same header may appear more than once */
+        while !sys_msg.is_null() {
+            let k = unsafe { *sys_msg };
+            msg.items.extend(msg_params_as_vec(k.k_items));
+            msg.exists = true;
+            sys_msg = k.k_next;
+        }
+        msg
+    }
+
+    pub fn items(&self) -> &Vec<String> {
+        &self.items
+    }
+    /// Whether `item` is in the list (case insensitive), eg: `100rel`.
+    pub fn contains(&self, item: &str) -> bool {
+        self.items.iter().any(|i| i.eq_ignore_ascii_case(item))
+    }
+}
+
+impl fmt::Display for MsgList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s: String = self.into();
+        write!(f, "{}", s)
+    }
+}
+
+impl Into<String> for &MsgList {
+    fn into(self) -> String {
+        self.items.join(", ")
+    }
+}
+
+/**********************************/
+/// Request line, eg: `INVITE sip:bob@example.com SIP/2.0`.
+#[derive(Default, Debug, Clone)]
+pub struct SipRequestLine {
+    method: String,
+    url: String,
+    version: String,
+}
+
+impl SipRequestLine {
+    pub(crate) fn _from_sys(sys_rq: *const sys::sip_request_t) -> Option<Self> {
+        if sys_rq.is_null() {
+            return None;
+        }
+        let sys_rq = unsafe { *sys_rq };
+        let sys_url_ptr: *const sys::url_t = &sys_rq.rq_url[0];
+
+        Some(Self {
+            method: cstr_as_option_string(sys_rq.rq_method_name).unwrap_or_default(),
+            url: url_as_string(sys_url_ptr),
+            version: cstr_as_option_string(sys_rq.rq_version).unwrap_or_default(),
+        })
+    }
+
+    pub fn method(&self) -> &String {
+        &self.method
+    }
+    /// Request-URI.
+    pub fn url(&self) -> &String {
+        &self.url
+    }
+    pub fn version(&self) -> &String {
+        &self.version
+    }
+}
+
+impl fmt::Display for SipRequestLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipRequestLine {
+    fn into(self) -> String {
+        format!("{} {} {}", self.method, self.url, self.version)
+    }
+}
+
+/**********************************/
+/// Status line, eg: `SIP/2.0 180 Ringing`.
+#[derive(Default, Debug, Clone)]
+pub struct SipStatusLine {
+    version: String,
+    status: u32,
+    phrase: String,
+}
+
+impl SipStatusLine {
+    pub(crate) fn _from_sys(sys_st: *const sys::sip_status_t) -> Option<Self> {
+        if sys_st.is_null() {
+            return None;
+        }
+        let sys_st = unsafe { *sys_st };
+
+        Some(Self {
+            version: cstr_as_option_string(sys_st.st_version).unwrap_or_default(),
+            status: sys_st.st_status as u32,
+            phrase: cstr_as_option_string(sys_st.st_phrase).unwrap_or_default(),
+        })
+    }
+
+    pub fn version(&self) -> &String {
+        &self.version
+    }
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+    pub fn phrase(&self) -> &String {
+        &self.phrase
+    }
+}
+
+impl fmt::Display for SipStatusLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipStatusLine {
+    fn into(self) -> String {
+        format!("{} {} {}", self.version, self.status, self.phrase)
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipCallId {
+    exists: bool,
+    id: String,
+}
+
+impl SipCallId {
+    pub(crate) fn _from_sys(sys_i: *const sys::sip_call_id_t) -> Self {
+        let mut call_id = Self::default();
+        if sys_i.is_null() {
+            return call_id;
+        }
+        let sys_i = unsafe { *sys_i };
+
+        assert!(!sys_i.i_id.is_null());
+        call_id.id = cstr_as_option_string(sys_i.i_id).unwrap();
+
+        call_id.exists = true;
+
+        call_id
+    }
+
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+}
+
+impl fmt::Display for SipCallId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Into<String> for &SipCallId {
+    fn into(self) -> String {
+        self.id.clone()
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipCSeq {
+    exists: bool,
+    seq: u32,
+    method: String,
+}
+
+impl SipCSeq {
+    pub(crate) fn _from_sys(sys_cs: *const sys::sip_cseq_t) -> Self {
+        let mut cseq = Self::default();
+        if sys_cs.is_null() {
+            return cseq;
+        }
+        let sys_cs = unsafe { *sys_cs };
+
+        cseq.seq = sys_cs.cs_seq;
+        cseq.method = cstr_as_option_string(sys_cs.cs_method_name).unwrap_or_default();
+
+        cseq.exists = true;
+
+        cseq
+    }
+
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+    pub fn method(&self) -> &String {
+        &self.method
+    }
+}
+
+impl fmt::Display for SipCSeq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipCSeq {
+    fn into(self) -> String {
+        if !self.exists {
+            return String::new();
+        }
+        format!("{} {}", self.seq, self.method)
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipMaxForwards {
+    exists: bool,
+    count: u32,
+}
+
+impl SipMaxForwards {
+    pub(crate) fn _from_sys(sys_mf: *const sys::sip_max_forwards_t) -> Self {
+        let mut max_forwards = Self::default();
+        if sys_mf.is_null() {
+            return max_forwards;
+        }
+        let sys_mf = unsafe { *sys_mf };
+
+        max_forwards.count = sys_mf.mf_count as u32;
+
+        max_forwards.exists = true;
+
+        max_forwards
+    }
+
+    /// Remaining hops, `None` if the message has no Max-Forwards header.
+    pub fn count(&self) -> Option<u32> {
+        if !self.exists {
+            return None;
+        }
+        Some(self.count)
+    }
+}
+
+impl fmt::Display for SipMaxForwards {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipMaxForwards {
+    fn into(self) -> String {
+        if !self.exists {
+            return String::new();
+        }
+        self.count.to_string()
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipExpires {
+    exists: bool,
+    delta: u32,
+}
+
+impl SipExpires {
+    pub(crate) fn _from_sys(sys_ex: *const sys::sip_expires_t) -> Self {
+        let mut expires = Self::default();
+        if sys_ex.is_null() {
+            return expires;
+        }
+        let sys_ex = unsafe { *sys_ex };
+
+        expires.delta = sys_ex.ex_delta as u32;
+
+        expires.exists = true;
+
+        expires
+    }
+
+    /// Expiry in seconds, `None` if the message has no Expires header.
+    pub fn delta(&self) -> Option<u32> {
+        if !self.exists {
+            return None;
+        }
+        Some(self.delta)
+    }
+}
+
+impl fmt::Display for SipExpires {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipExpires {
+    fn into(self) -> String {
+        if !self.exists {
+            return String::new();
+        }
+        self.delta.to_string()
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct SipVia {
+    protocol: String,
+    host: String,
+    port: Option<String>,
+    params: Vec<String>,
+    branch: Option<String>,
+    received: Option<String>,
+    rport: Option<String>,
+    maddr: Option<String>,
+}
+
+impl SipVia {
+    pub(crate) fn _from_sys(sys_via: *const sys::sip_via_t) -> Vec<Self> {
+        let mut list = Vec::new();
+        let mut sys_via = sys_via;
+        while !sys_via.is_null() {
+            let v = unsafe { *sys_via };
+            assert!(!v.v_protocol.is_null());
+            assert!(!v.v_host.is_null());
+            list.push(Self {
+                protocol: cstr_as_option_string(v.v_protocol).unwrap(),
+                host: cstr_as_option_string(v.v_host).unwrap(),
+                port: cstr_as_option_string(v.v_port),
+                params: msg_params_as_vec(v.v_params),
+                branch: cstr_as_option_string(v.v_branch),
+                received: cstr_as_option_string(v.v_received),
+                rport: cstr_as_option_string(v.v_rport),
+                maddr: cstr_as_option_string(v.v_maddr),
+            });
+            sys_via = v.v_next;
+        }
+        list
+    }
+
+    /// Protocol with transport, eg: `SIP/2.0/UDP`.
+    pub fn protocol(&self) -> &String {
+        &self.protocol
+    }
+    /// Transport part of the protocol, eg: `UDP`.
+    pub fn transport(&self) -> &str {
+        self.protocol.rsplit('/').next().unwrap_or("")
+    }
+    pub fn host(&self) -> &String {
+        &self.host
+    }
+    pub fn port(&self) -> Option<&String> {
+        self.port.as_ref()
+    }
+    /// Raw parameters, eg: `branch=z9hG4bK776asdhds`.
+    pub fn params(&self) -> &Vec<String> {
+        &self.params
+    }
+    pub fn branch(&self) -> Option<&String> {
+        self.branch.as_ref()
+    }
+    pub fn received(&self) -> Option<&String> {
+        self.received.as_ref()
+    }
+    /// Value of `rport`, empty if the parameter has no value.
+    pub fn rport(&self) -> Option<&String> {
+        self.rport.as_ref()
+    }
+    pub fn maddr(&self) -> Option<&String> {
+        self.maddr.as_ref()
+    }
+}
+
+impl fmt::Display for SipVia {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipVia {
+    fn into(self) -> String {
+        let mut r = format!("{} {}", self.protocol, self.host);
+        if let Some(port) = &self.port {
+            r.push(':');
+            r.push_str(port);
+        }
+        for param in &self.params {
+            r.push(';');
+            r.push_str(param);
+        }
+        r
+    }
+}
+
+/**********************************/
+/// A Route or Record-Route entry.
+#[derive(Default, Debug, Clone)]
+pub struct SipRoute {
+    display: String,
+    url: String,
+    params: Vec<String>,
+}
+
+impl SipRoute {
+    pub(crate) fn _from_sys(sys_route: *const sys::sip_route_t) -> Vec<Self> {
+        let mut list = Vec::new();
+        let mut sys_route = sys_route;
+        while !sys_route.is_null() {
+            let r = unsafe { *sys_route };
+            let sys_url_ptr: *const sys::url_t = &r.r_url[0];
+            list.push(Self {
+                display: cstr_as_option_string(r.r_display).unwrap_or_default(),
+                url: url_as_string(sys_url_ptr),
+                params: msg_params_as_vec(r.r_params),
+            });
+            sys_route = r.r_next;
+        }
+        list
+    }
+
+    pub fn display(&self) -> &String {
+        &self.display
+    }
+    pub fn url(&self) -> &String {
+        &self.url
+    }
+    pub fn params(&self) -> &Vec<String> {
+        &self.params
+    }
+    /// Whether the route is a loose router (`lr` parameter of the url, RFC 3261 16.12).
+    pub fn is_loose(&self) -> bool {
+        self.url
+            .split(';')
+            .skip(1)
+            .any(|param| param.eq_ignore_ascii_case("lr") || param.starts_with("lr="))
+    }
+}
+
+impl fmt::Display for SipRoute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipRoute {
+    fn into(self) -> String {
+        let mut r = if !self.display.is_empty() {
+            format!("{} <{}>", self.display, self.url)
+        } else {
+            format!("<{}>", self.url)
+        };
+        for param in &self.params {
+            r.push(';');
+            r.push_str(param);
+        }
+        r
+    }
+}
+
+/**********************************/
+/// Reason header (RFC 3326), eg: `Q.850;cause=16;text="Terminated"`.
+#[derive(Default, Debug, Clone)]
+pub struct SipReason {
+    protocol: String,
+    params: Vec<String>,
+    cause: Option<u32>,
+    text: Option<String>,
+}
+
+impl SipReason {
+    pub(crate) fn _from_sys(sys_re: *const sys::sip_reason_t) -> Vec<Self> {
+        let mut list = Vec::new();
+        let mut sys_re = sys_re;
+        while !sys_re.is_null() {
+            let re = unsafe { *sys_re };
+            assert!(!re.re_protocol.is_null());
+            list.push(Self {
+                protocol: cstr_as_option_string(re.re_protocol).unwrap(),
+                params: msg_params_as_vec(re.re_params),
+                cause: cstr_as_option_string(re.re_cause).and_then(|s| s.parse().ok()),
+                text: cstr_as_option_string(re.re_text).map(|s| s.trim_matches('"').to_string()),
+            });
+            sys_re = re.re_next;
+        }
+        list
+    }
+
+    /// Protocol of the cause, eg: `SIP` or `Q.850`.
+    pub fn protocol(&self) -> &String {
+        &self.protocol
+    }
+    pub fn params(&self) -> &Vec<String> {
+        &self.params
+    }
+    pub fn cause(&self) -> Option<u32> {
+        self.cause
+    }
+    /// Value of the `text` parameter, without quotes.
+    pub fn text(&self) -> Option<&String> {
+        self.text.as_ref()
+    }
+}
+
+impl fmt::Display for SipReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipReason {
+    fn into(self) -> String {
+        let mut r = self.protocol.clone();
+        for param in &self.params {
+            r.push(';');
+            r.push_str(param);
+        }
+        r
+    }
+}
+
+/**********************************/
+/// Warning header, eg: `307 isi.edu "Session parameter 'foo' not understood"`.
+#[derive(Default, Debug, Clone)]
+pub struct SipWarning {
+    code: u32,
+    host: String,
+    port: Option<String>,
+    text: String,
+}
+
+impl SipWarning {
+    pub(crate) fn _from_sys(sys_w: *const sys::sip_warning_t) -> Vec<Self> {
+        let mut list = Vec::new();
+        let mut sys_w = sys_w;
+        while !sys_w.is_null() {
+            let w = unsafe { *sys_w };
+            list.push(Self {
+                code: w.w_code,
+                host: cstr_as_option_string(w.w_host).unwrap_or_default(),
+                port: cstr_as_option_string(w.w_port),
+                text: cstr_as_option_string(w.w_text)
+                    .map(|s| s.trim_matches('"').to_string())
+                    .unwrap_or_default(),
+            });
+            sys_w = w.w_next;
+        }
+        list
+    }
+
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+    /// Host of the agent adding the warning.
+    pub fn host(&self) -> &String {
+        &self.host
+    }
+    pub fn port(&self) -> Option<&String> {
+        self.port.as_ref()
+    }
+    /// Warning text, without quotes.
+    pub fn text(&self) -> &String {
+        &self.text
+    }
+}
+
+impl fmt::Display for SipWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r: String = self.into();
+        write!(f, "{}", r)
+    }
+}
+
+impl Into<String> for &SipWarning {
+    fn into(self) -> String {
+        match &self.port {
+            Some(port) => format!("{} {}:{} \"{}\"", self.code, self.host, port, self.text),
+            None => format!("{} {} \"{}\"", self.code, self.host, self.text),
+        }
+    }
+}
+
+/**********************************/
+#[derive(Default, Debug, Clone)]
+pub struct Sip {
+    exists: bool,
+    from: SipAddr,
+    to: SipAddr,
+    subject: SipSubject,
+    content_type: SipContentType,
+    payload: SipPayload,
+    event: SipEvent,
+    subscription_state: SipSubscriptionState,
+    etag: SipETag,
+    if_match: SipIfMatch,
+    refer_to: SipAddr,
+    replaces: SipReplaces,
+    request: Option<SipRequestLine>,
+    status: Option<SipStatusLine>,
+    via: Vec<SipVia>,
+    route: Vec<SipRoute>,
+    record_route: Vec<SipRoute>,
+    max_forwards: SipMaxForwards,
+    call_id: SipCallId,
+    cseq: SipCSeq,
+    user_agent: SipUserAgent,
+    allow: SipAllow,
+    supported: SipSupported,
+    require: SipRequire,
+    reason: Vec<SipReason>,
+    warning: Vec<SipWarning>,
+    unknown: Vec<(String, String)>,
+    contacts: Vec<SipContact>,
+    expires: SipExpires,
+    www_authenticate: Vec<SipAuth>,
+    proxy_authenticate: Vec<SipAuth>,
+    authorization: Vec<SipAuth>,
+    proxy_authorization: Vec<SipAuth>,
+}
+impl Sip {
+    pub(crate) fn _from_sys(sys_sip: *const sys::sip_t) -> Self {
+        let mut sip = Self::default();
+        if sys_sip.is_null() {
+            return sip;
+        }
+
+        let sys_sip = unsafe { *sys_sip };
+
+        sip.from = SipAddr::_from_sys(sys_sip.sip_from);
+        sip.to = SipAddr::_from_sys(sys_sip.sip_to);
+
+        sip.subject = SipSubject::_from_sys(sys_sip.sip_subject);
+
+        sip.content_type = SipContentType::_from_sys(sys_sip.sip_content_type);
+
+        sip.payload = SipPayload::_from_sys(sys_sip.sip_payload);
+
+        sip.event = SipEvent::_from_sys(sys_sip.sip_event);
+        sip.subscription_state = SipSubscriptionState::_from_sys(sys_sip.sip_subscription_state);
+
+        sip.etag = SipETag::_from_sys(sys_sip.sip_etag);
+        sip.if_match = SipIfMatch::_from_sys(sys_sip.sip_if_match);
+
+        sip.refer_to = SipAddr::_from_sys_refer_to(sys_sip.sip_refer_to);
+        sip.replaces = SipReplaces::_from_sys(sys_sip.sip_replaces);
+
+        sip.contacts = SipContact::_from_sys(sys_sip.sip_contact);
+        sip.expires = SipExpires::_from_sys(sys_sip.sip_expires);
+
+        sip.www_authenticate = SipAuth::_from_sys(sys_sip.sip_www_authenticate);
+        sip.proxy_authenticate = SipAuth::_from_sys(sys_sip.sip_proxy_authenticate);
+        sip.authorization = SipAuth::_from_sys(sys_sip.sip_authorization);
+        sip.proxy_authorization = SipAuth::_from_sys(sys_sip.sip_proxy_authorization);
+
+        sip.request = SipRequestLine::_from_sys(sys_sip.sip_request);
+        sip.status = SipStatusLine::_from_sys(sys_sip.sip_status);
+
+        sip.via = SipVia::_from_sys(sys_sip.sip_via);
+        sip.route = SipRoute::_from_sys(sys_sip.sip_route);
+        sip.record_route = SipRoute::_from_sys(sys_sip.sip_record_route);
+        sip.max_forwards = SipMaxForwards::_from_sys(sys_sip.sip_max_forwards);
+
+        sip.call_id = SipCallId::_from_sys(sys_sip.sip_call_id);
+        sip.cseq = SipCSeq::_from_sys(sys_sip.sip_cseq);
+        sip.user_agent = SipUserAgent::_from_sys(sys_sip.sip_user_agent);
+
+        /* sip_allow_t starts as a msg_list_t (it adds a bitmap of methods) */
+        sip.allow = SipAllow::_from_sys(sys_sip.sip_allow as *const sys::msg_list_t);
+        sip.supported = SipSupported::_from_sys(sys_sip.sip_supported);
+        sip.require = SipRequire::_from_sys(sys_sip.sip_require);
+
+        sip.reason = SipReason::_from_sys(sys_sip.sip_reason);
+        sip.warning = SipWarning::_from_sys(sys_sip.sip_warning);
+
+        let mut sys_un = sys_sip.sip_unknown;
+        while !sys_un.is_null() {
+            let un = unsafe { *sys_un };
+            sip.unknown.push((
+                cstr_as_option_string(un.un_name).unwrap_or_default(),
+                cstr_as_option_string(un.un_value).unwrap_or_default(),
+            ));
+            sys_un = un.un_next;
+        }
+
+        sip.exists = true;
+        sip
+    }
+
+    pub fn from(&self) -> &SipAddr {
+        &self.from
+    }
+
+    pub fn to(&self) -> &SipAddr {
+        &self.to
+    }
+
+    pub fn subject(&self) -> &SipSubject {
+        &self.subject
+    }
+
+    pub fn content_type(&self) -> &SipContentType {
+        &self.content_type
+    }
+
+    pub fn payload(&self) -> &SipPayload {
+        &self.payload
+    }
+
+    pub fn event(&self) -> &SipEvent {
+        &self.event
+    }
+
+    pub fn subscription_state(&self) -> &SipSubscriptionState {
+        &self.subscription_state
+    }
+
+    pub fn etag(&self) -> &SipETag {
+        &self.etag
+    }
+
+    pub fn if_match(&self) -> &SipIfMatch {
+        &self.if_match
+    }
+
+    /// Method name of a request (eg: `INVITE`), `None` for responses.
+    pub fn method(&self) -> Option<&String> {
+        self.request.as_ref().map(|request| request.method())
+    }
+
+    /// Request line, `None` for responses.
+    pub fn request(&self) -> Option<&SipRequestLine> {
+        self.request.as_ref()
+    }
+
+    /// Status line, `None` for requests.
+    pub fn status(&self) -> Option<&SipStatusLine> {
+        self.status.as_ref()
+    }
+
+    /// Via headers, the topmost first.
+    pub fn via(&self) -> &[SipVia] {
+        &self.via
+    }
+
+    pub fn route(&self) -> &[SipRoute] {
+        &self.route
+    }
+
+    pub fn record_route(&self) -> &[SipRoute] {
+        &self.record_route
+    }
+
+    pub fn max_forwards(&self) -> &SipMaxForwards {
+        &self.max_forwards
+    }
+
+    pub fn call_id(&self) -> &SipCallId {
+        &self.call_id
+    }
+
+    pub fn cseq(&self) -> &SipCSeq {
+        &self.cseq
+    }
+
+    pub fn user_agent(&self) -> &SipUserAgent {
+        &self.user_agent
+    }
+
+    pub fn allow(&self) -> &SipAllow {
+        &self.allow
+    }
+
+    pub fn supported(&self) -> &SipSupported {
+        &self.supported
+    }
+
+    pub fn require(&self) -> &SipRequire {
+        &self.require
+    }
+
+    pub fn reason(&self) -> &[SipReason] {
+        &self.reason
+    }
+
+    pub fn warning(&self) -> &[SipWarning] {
+        &self.warning
+    }
+
+    /// Headers unknown to sofia-sip (eg: `P-Asserted-Identity`, `X-Account-Id`)
+    /// as `(name, value)`, in the order they were received.
+    pub fn unknown_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.unknown
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Value of the first unknown header `name` (case insensitive).
+    pub fn unknown_header(&self, name: &str) -> Option<&str> {
+        self.unknown_headers()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn refer_to(&self) -> &SipAddr {
+        &self.refer_to
+    }
+
+    pub fn replaces(&self) -> &SipReplaces {
+        &self.replaces
+    }
+
+    /// Contact headers, eg: the bindings in a response to REGISTER.
+    pub fn contacts(&self) -> &[SipContact] {
+        &self.contacts
+    }
+
+    pub fn expires(&self) -> &SipExpires {
+        &self.expires
+    }
+
+    /// Challenges of a 401 response.
+    pub fn www_authenticate(&self) -> &[SipAuth] {
+        &self.www_authenticate
+    }
+
+    /// Challenges of a 407 response.
+    pub fn proxy_authenticate(&self) -> &[SipAuth] {
+        &self.proxy_authenticate
+    }
+
+    pub fn authorization(&self) -> &[SipAuth] {
+        &self.authorization
+    }
+
+    pub fn proxy_authorization(&self) -> &[SipAuth] {
+        &self.proxy_authorization
+    }
+
+    /// Status line of a `message/sipfrag` payload, if any.
+    pub fn sipfrag(&self) -> Option<SipFrag> {
+        if !self
+            .content_type
+            .r#type
+            .eq_ignore_ascii_case("message/sipfrag")
+        {
+            return None;
+        }
+        SipFrag::parse(&self.payload.as_utf8_lossy())
+    }
+
+    /// DTMF event of an `application/dtmf-relay` or `application/dtmf` payload, if any.
+    pub fn dtmf(&self) -> Option<Dtmf> {
+        Dtmf::from_body(&self.content_type.r#type, &self.payload.as_utf8_lossy()).ok()
+    }
+}
+
+/**********************************/
+/// Borrowed view of the headers of a message, valid during a callback
+/// (see [`Nua::callback_ref`](crate::nua::Nua::callback_ref)).
+///
+/// Nothing is copied when the view is created: the text fields and the
+/// payload are borrowed from the message, the other headers are decoded
+/// when their method is called. [`SipRef::to_sip`] copies every header
+/// into a [`Sip`].
+#[derive(Clone, Copy, Debug)]
+pub struct SipRef<'a> {
+    ptr: *const sys::sip_t,
+    _marker: PhantomData<&'a sys::sip_t>,
+}
+
+/// Borrow a nullable c string for the lifetime of the message.
+fn cstr_as_cow<'a>(ptr: *const std::os::raw::c_char) -> Option<Cow<'a, str>> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy())
+}
+
+impl<'a> SipRef<'a> {
+    /// # Safety
+    /// `ptr` must be null or point to a `sip_t` valid for `'a`.
+    pub(crate) unsafe fn _from_sys(ptr: *const sys::sip_t) -> Self {
+        SipRef {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    fn sys(&self) -> Option<&'a sys::sip_t> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// Copy every header, the same as the `Sip` given to [`Nua::callback`](crate::nua::Nua::callback).
+    pub fn to_sip(self) -> Sip {
+        Sip::_from_sys(self.ptr)
+    }
+
+    /// Method name of a request (eg: `INVITE`), `None` for responses.
+    pub fn method(&self) -> Option<Cow<'a, str>> {
+        let rq = unsafe { self.sys()?.sip_request.as_ref() }?;
+        cstr_as_cow(rq.rq_method_name)
+    }
+
+    pub fn request(&self) -> Option<SipRequestLine> {
+        SipRequestLine::_from_sys(self.sys()?.sip_request)
+    }
+
+    pub fn status(&self) -> Option<SipStatusLine> {
+        SipStatusLine::_from_sys(self.sys()?.sip_status)
+    }
+
+    pub fn from(&self) -> SipAddr {
+        self.sys()
+            .map(|sip| SipAddr::_from_sys(sip.sip_from))
+            .unwrap_or_default()
+    }
+
+    pub fn to(&self) -> SipAddr {
+        self.sys()
+            .map(|sip| SipAddr::_from_sys(sip.sip_to))
+            .unwrap_or_default()
+    }
+
+    pub fn call_id(&self) -> SipCallId {
+        self.sys()
+            .map(|sip| SipCallId::_from_sys(sip.sip_call_id))
+            .unwrap_or_default()
+    }
+
+    pub fn cseq(&self) -> SipCSeq {
+        self.sys()
+            .map(|sip| SipCSeq::_from_sys(sip.sip_cseq))
+            .unwrap_or_default()
+    }
+
+    /// Via headers, the topmost first.
+    pub fn via(&self) -> Vec<SipVia> {
+        self.sys()
+            .map(|sip| SipVia::_from_sys(sip.sip_via))
+            .unwrap_or_default()
+    }
+
+    pub fn route(&self) -> Vec<SipRoute> {
+        self.sys()
+            .map(|sip| SipRoute::_from_sys(sip.sip_route))
+            .unwrap_or_default()
+    }
+
+    pub fn record_route(&self) -> Vec<SipRoute> {
+        self.sys()
+            .map(|sip| SipRoute::_from_sys(sip.sip_record_route))
+            .unwrap_or_default()
+    }
+
+    pub fn contacts(&self) -> Vec<SipContact> {
+        self.sys()
+            .map(|sip| SipContact::_from_sys(sip.sip_contact))
+            .unwrap_or_default()
+    }
+
+    pub fn max_forwards(&self) -> SipMaxForwards {
+        self.sys()
+            .map(|sip| SipMaxForwards::_from_sys(sip.sip_max_forwards))
+            .unwrap_or_default()
+    }
+
+    pub fn expires(&self) -> SipExpires {
+        self.sys()
+            .map(|sip| SipExpires::_from_sys(sip.sip_expires))
+            .unwrap_or_default()
+    }
+
+    pub fn subject(&self) -> Option<Cow<'a, str>> {
+        let g = unsafe { self.sys()?.sip_subject.as_ref() }?;
+        cstr_as_cow(g.g_string)
+    }
+
+    pub fn user_agent(&self) -> Option<Cow<'a, str>> {
+        let g = unsafe { self.sys()?.sip_user_agent.as_ref() }?;
+        cstr_as_cow(g.g_string)
+    }
+
+    /// Media type of the payload, eg: `text/plain`.
+    pub fn content_type(&self) -> Option<Cow<'a, str>> {
+        let c = unsafe { self.sys()?.sip_content_type.as_ref() }?;
+        cstr_as_cow(c.c_type)
+    }
+
+    /// The payload, empty if there is none.
+    pub fn payload(&self) -> &'a [u8] {
+        let pl = match self
+            .sys()
+            .and_then(|sip| unsafe { sip.sip_payload.as_ref() })
+        {
+            Some(pl) if !pl.pl_data.is_null() => pl,
+            _ => return &[],
+        };
+        unsafe { std::slice::from_raw_parts(pl.pl_data as *const u8, pl.pl_len as usize) }
+    }
+
+    pub fn event(&self) -> SipEvent {
+        self.sys()
+            .map(|sip| SipEvent::_from_sys(sip.sip_event))
+            .unwrap_or_default()
+    }
+
+    pub fn subscription_state(&self) -> SipSubscriptionState {
+        self.sys()
+            .map(|sip| SipSubscriptionState::_from_sys(sip.sip_subscription_state))
+            .unwrap_or_default()
+    }
+
+    /// Challenges of a 401 response.
+    pub fn www_authenticate(&self) -> Vec<SipAuth> {
+        self.sys()
+            .map(|sip| SipAuth::_from_sys(sip.sip_www_authenticate))
+            .unwrap_or_default()
+    }
+
+    /// Challenges of a 407 response.
+    pub fn proxy_authenticate(&self) -> Vec<SipAuth> {
+        self.sys()
+            .map(|sip| SipAuth::_from_sys(sip.sip_proxy_authenticate))
+            .unwrap_or_default()
+    }
+
+    pub fn supported(&self) -> MsgList {
+        self.sys()
+            .map(|sip| MsgList::_from_sys(sip.sip_supported))
+            .unwrap_or_default()
+    }
+
+    pub fn require(&self) -> MsgList {
+        self.sys()
+            .map(|sip| MsgList::_from_sys(sip.sip_require))
+            .unwrap_or_default()
+    }
+
+    pub fn reason(&self) -> Vec<SipReason> {
+        self.sys()
+            .map(|sip| SipReason::_from_sys(sip.sip_reason))
+            .unwrap_or_default()
+    }
+
+    /// Headers unknown to sofia-sip as `(name, value)`, see [`Sip::unknown_headers`].
+    pub fn unknown_headers(&self) -> impl Iterator<Item = (Cow<'a, str>, Cow<'a, str>)> {
+        let first = self
+            .sys()
+            .map_or(std::ptr::null_mut(), |sip| sip.sip_unknown);
+        let mut sys_un = first as *const sys::sip_unknown_t;
+        std::iter::from_fn(move || {
+            let un: &'a sys::sip_unknown_t = unsafe { sys_un.as_ref() }?;
+            sys_un = un.un_next;
+            Some((
+                cstr_as_cow(un.un_name).unwrap_or_default(),
+                cstr_as_cow(un.un_value).unwrap_or_default(),
+            ))
+        })
+    }
+
+    /// Value of the first unknown header `name` (case insensitive).
+    pub fn unknown_header(&self, name: &str) -> Option<Cow<'a, str>> {
+        self.unknown_headers()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+}
+
+/**********************************/
+/// A SIP message outside of a NUA stack, parsed (eg: from a capture) or
+/// built by the application.
+#[derive(Debug)]
+pub struct Message {
+    c_ptr: *mut sys::msg_t,
+    /* bytes after the message given to `parse` */
+    trailing: usize,
+}
+
+impl Message {
+    fn _create() -> Result<Self> {
+        let c_ptr = unsafe { sys::msg_create(sys::sip_default_mclass(), 0) };
+        if c_ptr.is_null() {
+            return Err(Error::CreateMessageError);
+        }
+        Ok(Message { c_ptr, trailing: 0 })
+    }
+
+    /* the message is a home: its headers are freed with it */
+    fn home(&self) -> *mut sys::su_home_t {
+        self.c_ptr as *mut sys::su_home_t
+    }
+
+    fn pub_ptr(&self) -> *mut sys::msg_pub_t {
+        unsafe { sys::msg_object(self.c_ptr) }
+    }
+
+    /* parse `s` as a header of class `hc` and add it to the message */
+    fn _insert_make(&mut self, hc: *mut sys::msg_hclass_t, s: &str) -> Result<()> {
+        let s = CString::new(s)?;
+        let h = unsafe { sys::msg_header_make(self.home(), hc, s.as_ptr()) };
+        if h.is_null() {
+            return Err(Error::InvalidHeaderError);
+        }
+        if unsafe { sys::msg_header_insert(self.c_ptr, self.pub_ptr(), h) } < 0 {
+            return Err(Error::InvalidHeaderError);
+        }
+        Ok(())
+    }
+
+    /// Create a request without headers, eg: `Message::request("OPTIONS", "sip:bob@example.com")`.
+    pub fn request(method: &str, url: &str) -> Result<Self> {
+        let mut message = Self::_create()?;
+        let line = format!("{} {} SIP/2.0", method, url);
+        message._insert_make(unsafe { sys::sip_request_class.as_mut_ptr() }, &line)?;
+        Ok(message)
+    }
+
+    /// Create a response without headers, eg: `Message::response(200, "OK")`.
+    pub fn response(status: u32, phrase: &str) -> Result<Self> {
+        let mut message = Self::_create()?;
+        let line = format!("SIP/2.0 {} {}", status, phrase);
+        message._insert_make(unsafe { sys::sip_status_class.as_mut_ptr() }, &line)?;
+        Ok(message)
+    }
+
+    /// Parse a request or a response with sofia-sip parser.
+    ///
+    /// Only the first message of `data` is parsed, the size of the rest is
+    /// given by [`Message::trailing_len`]. A message missing one of
+    /// the headers present in every request and response (From, To,
+    /// Call-ID, CSeq, Via) is refused; the other headers that can not be
+    /// parsed are kept in [`Message::errors`].
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Err(Error::IncompleteMessageError);
+        }
+        /* destroyed on error */
+        let mut message = Self::_create()?;
+        let c_ptr = message.c_ptr;
+
+        let size = data.len() as sys::usize_t;
+        let buf = unsafe { sys::msg_buf_alloc(c_ptr, size) };
+        if buf.is_null() {
+            return Err(Error::ParseMessageError);
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), buf as *mut u8, data.len());
+            sys::msg_buf_commit(c_ptr, size, 1 /* end of stream */);
+        }
+
+        match unsafe { sys::msg_extract(c_ptr) } {
+            x if x < 0 => return Err(Error::ParseMessageError),
+            0 => return Err(Error::IncompleteMessageError),
+            _ => {}
+        }
+        /* committed but not consumed by the first message */
+        message.trailing = unsafe { sys::msg_buf_committed(c_ptr) } as usize;
+
+        let sys_sip = message.sys_sip();
+        if sys_sip.is_null() {
+            return Err(Error::ParseMessageError);
+        }
+        let sys_sip = unsafe { *sys_sip };
+        if sys_sip.sip_request.is_null() && sys_sip.sip_status.is_null() {
+            return Err(Error::ParseMessageError);
+        }
+        if sys_sip.sip_from.is_null()
+            || sys_sip.sip_to.is_null()
+            || sys_sip.sip_call_id.is_null()
+            || sys_sip.sip_cseq.is_null()
+            || sys_sip.sip_via.is_null()
+        {
+            return Err(Error::ParseMessageError);
+        }
+        /* a body shorter than its Content-Length */
+        if !sys_sip.sip_content_length.is_null() {
+            let length = unsafe { *sys_sip.sip_content_length }.l_length as usize;
+            let payload = MsgPayload::_from_sys(sys_sip.sip_payload);
+            if payload.data.len() < length {
+                return Err(Error::IncompleteMessageError);
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// Number of bytes given to [`Message::parse`] after the end of the
+    /// message, they are ignored (eg: the extra octets of a UDP datagram,
+    /// RFC 4475 section 3.1.1.8).
+    pub fn trailing_len(&self) -> usize {
+        self.trailing
+    }
+
+    pub(crate) fn sys_sip(&self) -> *const sys::sip_t {
+        self.pub_ptr() as *const sys::sip_t
+    }
+
+    /// Typed view of the headers, the same given to the NUA callbacks.
+    pub fn sip(&self) -> Sip {
+        Sip::_from_sys(self.sys_sip())
+    }
+
+    /// Borrowed view of the headers, see [`SipRef`].
+    pub fn sip_ref(&self) -> SipRef<'_> {
+        unsafe { SipRef::_from_sys(self.sys_sip()) }
+    }
+
+    /// Add a header, eg: `add_header("Max-Forwards", "70")`.
+    ///
+    /// A header with a list of values (eg: Via) may be given more than
+    /// once, its values are appended.
+    pub fn add_header(&mut self, name: &str, value: &str) -> Result<()> {
+        let s = CString::new(format!("{}: {}", name, value))?;
+        if unsafe { sys::msg_header_add_str(self.c_ptr, self.pub_ptr(), s.as_ptr()) } < 0 {
+            return Err(Error::InvalidHeaderError);
+        }
+        Ok(())
+    }
+
+    /// Remove every header `name` (case insensitive, compact forms are
+    /// accepted), returns `false` if there is none.
+    pub fn remove_header(&mut self, name: &str) -> Result<bool> {
+        let c_name = CString::new(name)?;
+        let mclass = unsafe { sys::sip_default_mclass() };
+        let hc = unsafe { sys::msg_find_hclass(mclass, c_name.as_ptr(), std::ptr::null_mut()) };
+        if hc.is_null() {
+            return Ok(false);
+        }
+
+        if hc == unsafe { sys::sip_unknown_class.as_mut_ptr() } {
+            /* extension headers share the same list, remove only `name` */
+            let mut found = Vec::new();
+            let mut sys_un = unsafe { *(self.pub_ptr() as *const sys::sip_t) }.sip_unknown;
+            while !sys_un.is_null() {
+                let un = unsafe { *sys_un };
+                let un_name = cstr_as_option_string(un.un_name).unwrap_or_default();
+                if un_name.eq_ignore_ascii_case(name) {
+                    found.push(sys_un as *mut sys::msg_header_t);
+                }
+                sys_un = un.un_next;
+            }
+            for h in &found {
+                unsafe { sys::msg_header_remove(self.c_ptr, self.pub_ptr(), *h) };
+            }
+            return Ok(!found.is_empty());
+        }
+
+        let slot = unsafe { sys::msg_hclass_offset(mclass, self.pub_ptr(), hc) };
+        if slot.is_null() || unsafe { *slot }.is_null() {
+            return Ok(false);
+        }
+        unsafe { sys::msg_header_remove_all(self.c_ptr, self.pub_ptr(), *slot) };
+        Ok(true)
+    }
+
+    /// Replace every header `name` by a single one with `value`.
+    pub fn replace_header(&mut self, name: &str, value: &str) -> Result<()> {
+        self.remove_header(name)?;
+        self.add_header(name, value)
+    }
+
+    /// Replace the body and its Content-Type.
+    pub fn set_body(&mut self, content_type: &str, data: &[u8]) -> Result<()> {
+        self.remove_body()?;
+        self.replace_header("Content-Type", content_type)?;
+        let pl = unsafe {
+            sys::sip_payload_create(
+                self.home(),
+                data.as_ptr() as *const std::os::raw::c_void,
+                data.len() as sys::isize_t,
+            )
+        };
+        if pl.is_null() {
+            return Err(Error::InvalidHeaderError);
+        }
+        let h = pl as *mut sys::msg_header_t;
+        if unsafe { sys::msg_header_insert(self.c_ptr, self.pub_ptr(), h) } < 0 {
+            return Err(Error::InvalidHeaderError);
+        }
+        Ok(())
+    }
+
+    /// Remove the body and its Content-Type.
+    pub fn remove_body(&mut self) -> Result<()> {
+        let sys_sip = unsafe { *(self.pub_ptr() as *const sys::sip_t) };
+        if !sys_sip.sip_payload.is_null() {
+            let h = sys_sip.sip_payload as *mut sys::msg_header_t;
+            unsafe { sys::msg_header_remove(self.c_ptr, self.pub_ptr(), h) };
+        }
+        self.remove_header("Content-Type")?;
+        Ok(())
+    }
+
+    /// Render the message in wire format.
+    ///
+    /// Content-Length is set to the size of the body.
+    pub fn to_bytes(&mut self) -> Result<Vec<u8>> {
+        /* separator and Content-Length, fails without From, To, Call-ID or CSeq */
+        if unsafe { sys::sip_complete_message(self.c_ptr) } < 0 {
+            return Err(Error::SerializeMessageError);
+        }
+        if unsafe { sys::msg_serialize(self.c_ptr, self.pub_ptr()) } < 0 {
+            return Err(Error::SerializeMessageError);
+        }
+        let mut len: sys::usize_t = 0;
+        let s = unsafe { sys::msg_as_string(self.home(), self.c_ptr, self.pub_ptr(), 0, &mut len) };
+        if s.is_null() {
+            return Err(Error::SerializeMessageError);
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(s as *const u8, len as usize).to_vec() };
+        unsafe { sys::su_free(self.home(), s as *mut std::os::raw::c_void) };
+        Ok(bytes)
+    }
+
+    /// Names of the headers that could not be parsed and of the extra
+    /// instances of the headers allowed only once (eg: a second CSeq).
+    pub fn errors(&self) -> Vec<String> {
+        let mut list = Vec::new();
+        let sys_sip = self.sys_sip();
+        if sys_sip.is_null() {
+            return list;
+        }
+        let error_class = unsafe { sys::sip_error_class.as_mut_ptr() } as *const sys::msg_hclass_t;
+        let mut sys_error = unsafe { *sys_sip }.sip_error;
+        while !sys_error.is_null() {
+            let er = unsafe { *sys_error };
+            let common = er.er_common[0];
+            if common.h_class == error_class {
+                list.push(cstr_as_option_string(er.er_name).unwrap_or_default());
+            } else {
+                /* a duplicate keeps its own class (and layout), its name is
+                only found in the text it was parsed from */
+                list.push(raw_header_name(&common));
+            }
+            sys_error = er.er_next;
+        }
+        list
+    }
+}
+
+/* name of a parsed header, from its original text `Name: value` */
+fn raw_header_name(common: &sys::msg_common_t) -> String {
+    if common.h_data.is_null() {
+        return String::new();
+    }
+    let data =
+        unsafe { std::slice::from_raw_parts(common.h_data as *const u8, common.h_len as usize) };
+    let name = data.split(|c| *c == b':').next().unwrap_or_default();
+    String::from_utf8_lossy(name).trim().to_string()
+}
+
+impl Drop for Message {
+    fn drop(&mut self) {
+        unsafe { sys::msg_destroy(self.c_ptr) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_param() {
+        let auth = SipAuth {
+            scheme: "Digest".to_string(),
+            params: vec![
+                "realm=\"example.com\"".to_string(),
+                "nonce=\"abc\"".to_string(),
+                "algorithm=MD5".to_string(),
+            ],
+        };
+        assert_eq!(auth.realm(), Some("example.com".to_string()));
+        assert_eq!(auth.param("ALGORITHM"), Some("MD5".to_string()));
+        assert_eq!(auth.param("opaque"), None);
+        assert_eq!(
+            auth.to_string(),
+            "Digest realm=\"example.com\", nonce=\"abc\", algorithm=MD5"
+        );
+    }
+
+    #[test]
+    fn test_parse_sipfrag() {
+        let frag = SipFrag::parse("SIP/2.0 180 Ringing\r\n").unwrap();
+        assert_eq!(frag.status(), 180);
+        assert_eq!(frag.phrase(), "Ringing");
+
+        let frag = SipFrag::parse("SIP/2.0 200 OK\r\nContent-Length: 0\r\n").unwrap();
+        assert_eq!(frag.status(), 200);
+        assert_eq!(frag.to_string(), "SIP/2.0 200 OK");
+    }
+
+    #[test]
+    fn test_parse_invalid_sipfrag() {
+        assert_eq!(SipFrag::parse(""), None);
+        assert_eq!(SipFrag::parse("INVITE sip:bob@example.com SIP/2.0"), None);
+        assert_eq!(SipFrag::parse("SIP/2.0 abc Ringing"), None);
+    }
+
+    #[test]
+    fn test_via_from_sys() {
+        let protocol = CString::new("SIP/2.0/UDP").unwrap();
+        let host = CString::new("pc33.atlanta.com").unwrap();
+        let port = CString::new("5066").unwrap();
+        let branch = CString::new("branch=z9hG4bK776asdhds").unwrap();
+        let rport = CString::new("rport").unwrap();
+        let params = [branch.as_ptr(), rport.as_ptr(), std::ptr::null()];
+
+        let mut second: sys::sip_via_t = unsafe { std::mem::zeroed() };
+        second.v_protocol = protocol.as_ptr();
+        second.v_host = host.as_ptr();
+        let mut first: sys::sip_via_t = unsafe { std::mem::zeroed() };
+        first.v_next = &mut second;
+        first.v_protocol = protocol.as_ptr();
+        first.v_host = host.as_ptr();
+        first.v_port = port.as_ptr();
+        first.v_params = params.as_ptr();
+        first.v_branch = branch.as_ptr().wrapping_add("branch=".len());
+        first.v_rport = rport.as_ptr().wrapping_add("rport".len());
+
+        let via = SipVia::_from_sys(&first);
+        assert_eq!(via.len(), 2);
+        assert_eq!(via[0].transport(), "UDP");
+        assert_eq!(via[0].branch().unwrap(), "z9hG4bK776asdhds");
+        assert_eq!(via[0].rport().unwrap(), "");
+        assert_eq!(via[0].received(), None);
+        assert_eq!(
+            via[0].to_string(),
+            "SIP/2.0/UDP pc33.atlanta.com:5066;branch=z9hG4bK776asdhds;rport"
+        );
+        assert_eq!(via[1].to_string(), "SIP/2.0/UDP pc33.atlanta.com");
+    }
+
+    #[test]
+    fn test_call_id_and_cseq_from_sys() {
+        let id = CString::new("a84b4c76e66710@pc33.atlanta.com").unwrap();
+        let mut sys_i: sys::sip_call_id_t = unsafe { std::mem::zeroed() };
+        sys_i.i_id = id.as_ptr();
+        let call_id = SipCallId::_from_sys(&sys_i);
+        assert_eq!(call_id.id(), "a84b4c76e66710@pc33.atlanta.com");
+
+        let method = CString::new("INVITE").unwrap();
+        let mut sys_cs: sys::sip_cseq_t = unsafe { std::mem::zeroed() };
+        sys_cs.cs_seq = 314159;
+        sys_cs.cs_method_name = method.as_ptr();
+        let cseq = SipCSeq::_from_sys(&sys_cs);
+        assert_eq!(cseq.seq(), 314159);
+        assert_eq!(cseq.to_string(), "314159 INVITE");
+
+        assert_eq!(SipCSeq::_from_sys(std::ptr::null()).to_string(), "");
+    }
+
+    #[test]
+    fn test_max_forwards_and_expires_from_sys() {
+        let mut sys_mf: sys::sip_max_forwards_t = unsafe { std::mem::zeroed() };
+        sys_mf.mf_count = 70;
+        let max_forwards = SipMaxForwards::_from_sys(&sys_mf);
+        assert_eq!(max_forwards.count(), Some(70));
+        assert_eq!(max_forwards.to_string(), "70");
+
+        let mut sys_ex: sys::sip_expires_t = unsafe { std::mem::zeroed() };
+        sys_ex.ex_delta = 3600;
+        let expires = SipExpires::_from_sys(&sys_ex);
+        assert_eq!(expires.delta(), Some(3600));
+        assert_eq!(expires.to_string(), "3600");
+
+        assert_eq!(SipMaxForwards::_from_sys(std::ptr::null()).count(), None);
+        assert_eq!(SipExpires::_from_sys(std::ptr::null()).delta(), None);
+        assert_eq!(SipExpires::_from_sys(std::ptr::null()).to_string(), "");
+    }
+
+    #[test]
+    fn test_list_from_sys() {
+        let timer = CString::new("timer").unwrap();
+        let rel = CString::new("100rel").unwrap();
+        let replaces = CString::new("replaces").unwrap();
+        let items1 = [timer.as_ptr(), rel.as_ptr(), std::ptr::null()];
+        let items2 = [replaces.as_ptr(), std::ptr::null()];
+
+        let mut second: sys::msg_list_t = unsafe { std::mem::zeroed() };
+        second.k_items = items2.as_ptr() as *mut sys::msg_param_t;
+        let mut first: sys::msg_list_t = unsafe { std::mem::zeroed() };
+        first.k_next = &mut second;
+        first.k_items = items1.as_ptr() as *mut sys::msg_param_t;
+
+        let supported = MsgList::_from_sys(&first);
+        assert!(supported.contains("100REL"));
+        assert!(!supported.contains("path"));
+        assert_eq!(supported.to_string(), "timer, 100rel, replaces");
+    }
+
+    #[test]
+    fn test_reason_and_warning_from_sys() {
+        let protocol = CString::new("Q.850").unwrap();
+        let cause = CString::new("cause=16").unwrap();
+        let text = CString::new("text=\"Terminated\"").unwrap();
+        let params = [cause.as_ptr(), text.as_ptr(), std::ptr::null()];
+        let mut sys_re: sys::sip_reason_t = unsafe { std::mem::zeroed() };
+        sys_re.re_protocol = protocol.as_ptr();
+        sys_re.re_params = params.as_ptr();
+        sys_re.re_cause = cause.as_ptr().wrapping_add("cause=".len());
+        sys_re.re_text = text.as_ptr().wrapping_add("text=".len());
+        let reason = SipReason::_from_sys(&sys_re);
+        assert_eq!(reason.len(), 1);
+        assert_eq!(reason[0].cause(), Some(16));
+        assert_eq!(reason[0].text().unwrap(), "Terminated");
+        assert_eq!(reason[0].to_string(), "Q.850;cause=16;text=\"Terminated\"");
+
+        let host = CString::new("isi.edu").unwrap();
+        let text = CString::new("\"Session parameter 'foo' not understood\"").unwrap();
+        let mut sys_w: sys::sip_warning_t = unsafe { std::mem::zeroed() };
+        sys_w.w_code = 307;
+        sys_w.w_host = host.as_ptr();
+        sys_w.w_text = text.as_ptr();
+        let warning = SipWarning::_from_sys(&sys_w);
+        assert_eq!(warning[0].code(), 307);
+        assert_eq!(warning[0].text(), "Session parameter 'foo' not understood");
+        assert_eq!(
+            warning[0].to_string(),
+            "307 isi.edu \"Session parameter 'foo' not understood\""
+        );
+    }
+
+    #[test]
+    fn test_status_line_from_sys() {
+        let version = CString::new("SIP/2.0").unwrap();
+        let phrase = CString::new("Ringing").unwrap();
+        let mut sys_st: sys::sip_status_t = unsafe { std::mem::zeroed() };
+        sys_st.st_version = version.as_ptr();
+        sys_st.st_status = 180;
+        sys_st.st_phrase = phrase.as_ptr();
+        let status = SipStatusLine::_from_sys(&sys_st).unwrap();
+        assert_eq!(status.status(), 180);
+        assert_eq!(status.to_string(), "SIP/2.0 180 Ringing");
+        assert!(SipStatusLine::_from_sys(std::ptr::null()).is_none());
+    }
+
+    #[test]
+    fn test_route_is_loose() {
+        let route = SipRoute {
+            display: String::new(),
+            url: "sip:p1.example.com;lr".to_string(),
+            params: Vec::new(),
+        };
+        assert!(route.is_loose());
+        assert_eq!(route.to_string(), "<sip:p1.example.com;lr>");
+        let route = SipRoute {
+            url: "sip:p2.example.com;transport=tcp".to_string(),
+            ..route
+        };
+        assert!(!route.is_loose());
+    }
+
+    /* RFC 4475 torture messages, the parser does not need `su::init` */
+    macro_rules! torture {
+        ($name:expr) => {
+            include_bytes!(concat!("../tests/rfc4475/", $name, ".dat"))
+        };
+    }
+
+    /* outcome of `Message::parse` for a torture message */
+    #[derive(Debug)]
+    enum Parsed {
+        /* parsed, every header decoded */
+        Valid,
+        /* parsed, the header is listed by `Message::errors` */
+        BadHeader(&'static str),
+        /* refused, a mandatory header or the start line can not be parsed */
+        Rejected,
+        /* refused, the body is shorter than its Content-Length */
+        Incomplete,
+    }
+
+    /* every message of RFC 4475, the RFC marks some of them invalid for a
+    SIP element but sofia-sip parses them (they are valid syntax or the
+    parser is lenient), their outcome is the one expected from sofia-sip */
+    const RFC4475: &[(&str, &[u8], Parsed)] = &[
+        /* 3.1.1 valid messages */
+        ("wsinv", torture!("wsinv"), Parsed::Valid),
+        ("intmeth", torture!("intmeth"), Parsed::Valid),
+        ("esc01", torture!("esc01"), Parsed::Valid),
+        ("escnull", torture!("escnull"), Parsed::Valid),
+        ("esc02", torture!("esc02"), Parsed::Valid),
+        ("lwsdisp", torture!("lwsdisp"), Parsed::Valid),
+        ("longreq", torture!("longreq"), Parsed::Valid),
+        ("dblreq", torture!("dblreq"), Parsed::Valid),
+        ("semiuri", torture!("semiuri"), Parsed::Valid),
+        ("transports", torture!("transports"), Parsed::Valid),
+        ("mpart01", torture!("mpart01"), Parsed::Valid),
+        ("unreason", torture!("unreason"), Parsed::Valid),
+        ("noreason", torture!("noreason"), Parsed::Valid),
+        /* 3.1.2 invalid messages */
+        ("badinv01", torture!("badinv01"), Parsed::Rejected),
+        ("clerr", torture!("clerr"), Parsed::Incomplete),
+        ("ncl", torture!("ncl"), Parsed::BadHeader("Content-Length")),
+        ("scalar02", torture!("scalar02"), Parsed::Rejected),
+        ("scalarlg", torture!("scalarlg"), Parsed::Rejected),
+        ("quotbal", torture!("quotbal"), Parsed::Rejected),
+        ("ltgtruri", torture!("ltgtruri"), Parsed::Rejected),
+        ("lwsruri", torture!("lwsruri"), Parsed::Rejected),
+        ("lwsstart", torture!("lwsstart"), Parsed::Valid),
+        ("trws", torture!("trws"), Parsed::Valid),
+        ("escruri", torture!("escruri"), Parsed::Valid),
+        ("baddate", torture!("baddate"), Parsed::BadHeader("Date")),
+        ("regbadct", torture!("regbadct"), Parsed::Valid),
+        ("badaspec", torture!("badaspec"), Parsed::Rejected),
+        ("baddn", torture!("baddn"), Parsed::Rejected),
+        ("badvers", torture!("badvers"), Parsed::Valid),
+        ("mismatch01", torture!("mismatch01"), Parsed::Valid),
+        ("mismatch02", torture!("mismatch02"), Parsed::Valid),
+        ("bigcode", torture!("bigcode"), Parsed::Rejected),
+        /* 3.2 transaction layer semantics */
+        ("badbranch", torture!("badbranch"), Parsed::Valid),
+        /* 3.3 application layer semantics */
+        ("insuf", torture!("insuf"), Parsed::Rejected),
+        ("unkscm", torture!("unkscm"), Parsed::Valid),
+        ("novelsc", torture!("novelsc"), Parsed::Valid),
+        ("unksm2", torture!("unksm2"), Parsed::Valid),
+        ("bext01", torture!("bext01"), Parsed::Valid),
+        ("invut", torture!("invut"), Parsed::Valid),
+        ("regaut01", torture!("regaut01"), Parsed::Valid),
+        ("multi01", torture!("multi01"), Parsed::BadHeader("CSeq")),
+        (
+            "mcl01",
+            torture!("mcl01"),
+            Parsed::BadHeader("Content-Length"),
+        ),
+        ("bcast", torture!("bcast"), Parsed::Valid),
+        ("zeromf", torture!("zeromf"), Parsed::Valid),
+        ("cparam01", torture!("cparam01"), Parsed::Valid),
+        ("cparam02", torture!("cparam02"), Parsed::Valid),
+        ("regescrt", torture!("regescrt"), Parsed::Valid),
+        ("sdp01", torture!("sdp01"), Parsed::Valid),
+        /* 3.4 backward compatibility */
+        ("inv2543", torture!("inv2543"), Parsed::Valid),
+    ];
+
+    #[test]
+    fn test_parse_rfc4475() {
+        for (name, data, expected) in RFC4475 {
+            let result = Message::parse(data);
+            match (expected, &result) {
+                (Parsed::Valid, Ok(message)) => {
+                    assert!(
+                        message.errors().is_empty(),
+                        "{}: {:?}",
+                        name,
+                        message.errors()
+                    )
+                }
+                (Parsed::BadHeader(header), Ok(message)) => assert!(
+                    message
+                        .errors()
+                        .iter()
+                        .any(|e| e.eq_ignore_ascii_case(header)),
+                    "{}: {:?}",
+                    name,
+                    message.errors()
+                ),
+                (Parsed::Rejected, Err(Error::ParseMessageError)) => {}
+                (Parsed::Incomplete, Err(Error::IncompleteMessageError)) => {}
+                _ => panic!("{}: expected {:?}, got {:?}", name, expected, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_rfc4475_details() {
+        /* the INVITE after the REGISTER is not parsed */
+        let message = Message::parse(torture!("dblreq")).unwrap();
+        assert_eq!(message.sip().method().unwrap(), "REGISTER");
+        assert!(message.trailing_len() > 0);
+
+        /* the first instance of a header allowed once is kept */
+        let sip = Message::parse(torture!("multi01")).unwrap().sip();
+        assert_eq!(sip.cseq().seq(), 5);
+        assert_eq!(sip.call_id().id(), "multi01.98asdh@192.0.2.1");
+
+        /* no Content-Length: the body extends to the end */
+        let sip = Message::parse(torture!("inv2543")).unwrap().sip();
+        assert!(sip.payload().as_utf8_lossy().ends_with("RTP/AVP 0\r\n"));
+    }
+
+    #[test]
+    fn test_parse_wsinv() {
+        let sip = Message::parse(torture!("wsinv")).unwrap().sip();
+        assert_eq!(sip.method().unwrap(), "INVITE");
+        assert_eq!(sip.call_id().id(), "wsinv.ndaksdj@192.0.2.1");
+        assert_eq!(sip.cseq().seq(), 9);
+        assert_eq!(sip.cseq().method(), "INVITE");
+        assert_eq!(sip.max_forwards().count(), Some(68));
+        assert_eq!(sip.via().len(), 3);
+        assert_eq!(sip.via()[1].transport(), "TCP");
+        assert_eq!(sip.route().len(), 1);
+        assert!(sip.route()[0].is_loose());
+        assert_eq!(sip.contacts()[0].q().unwrap(), "0.33");
+        assert_eq!(sip.payload().as_utf8_lossy().len(), 150);
+    }
+
+    #[test]
+    fn test_parse_esc01() {
+        let message = Message::parse(torture!("esc01")).unwrap();
+        assert!(message.errors().is_empty());
+        let sip = message.sip();
+        assert_eq!(sip.call_id().id(), "esc01.239409asdfakjkn23onasd0-3234");
+        assert_eq!(sip.cseq().seq(), 234234);
+        assert_eq!(sip.max_forwards().count(), Some(87));
+        assert_eq!(sip.contacts().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let sip = Message::parse(torture!("noreason")).unwrap().sip();
+        assert!(sip.method().is_none());
+        let status = sip.status().unwrap();
+        assert_eq!(status.status(), 100);
+        assert_eq!(status.phrase(), "");
+        assert_eq!(sip.cseq().to_string(), "35 INVITE");
+
+        let sip = Message::parse(torture!("unreason")).unwrap().sip();
+        let status = sip.status().unwrap();
+        assert_eq!(status.status(), 200);
+        assert!(status.phrase().ends_with("простое"));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            Message::parse(b""),
+            Err(Error::IncompleteMessageError)
+        ));
+        assert!(Message::parse(b"this is not a SIP message\r\n\r\n").is_err());
+    }
+
+    fn message_request() -> Message {
+        let mut message = Message::request("MESSAGE", "sip:bob@example.com").unwrap();
+        message
+            .add_header("Via", "SIP/2.0/UDP 192.0.2.1;branch=z9hG4bK776asdhds")
+            .unwrap();
+        message.add_header("Max-Forwards", "70").unwrap();
+        message
+            .add_header("From", "<sip:alice@example.com>;tag=1928301774")
+            .unwrap();
+        message.add_header("To", "<sip:bob@example.com>").unwrap();
+        message
+            .add_header("Call-ID", "a84b4c76e66710@pc33.example.com")
+            .unwrap();
+        message.add_header("CSeq", "1 MESSAGE").unwrap();
+        message.add_header("X-Test", "one").unwrap();
+        message
+    }
+
+    #[test]
+    fn test_build_request() {
+        let mut message = message_request();
+        message.set_body("text/plain", b"hello").unwrap();
+        let bytes = message.to_bytes().unwrap();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.starts_with("MESSAGE sip:bob@example.com SIP/2.0\r\n"));
+        assert!(text.ends_with("\r\n\r\nhello"));
+
+        let sip = Message::parse(&bytes).unwrap().sip();
+        assert_eq!(sip.method().unwrap(), "MESSAGE");
+        assert_eq!(sip.cseq().to_string(), "1 MESSAGE");
+        assert_eq!(sip.max_forwards().count(), Some(70));
+        assert_eq!(sip.via()[0].branch().unwrap(), "z9hG4bK776asdhds");
+        assert_eq!(sip.content_type().to_string(), "text/plain");
+        assert_eq!(sip.payload().as_utf8_lossy(), "hello");
+    }
+
+    #[test]
+    fn test_build_response() {
+        let mut message = Message::response(486, "Busy Here").unwrap();
+        message
+            .add_header("Via", "SIP/2.0/UDP 192.0.2.1;branch=z9hG4bK776asdhds")
+            .unwrap();
+        message
+            .add_header("From", "<sip:alice@example.com>;tag=1")
+            .unwrap();
+        message
+            .add_header("To", "<sip:bob@example.com>;tag=2")
+            .unwrap();
+        message.add_header("Call-ID", "486@example.com").unwrap();
+        message.add_header("CSeq", "1 INVITE").unwrap();
+        let bytes = message.to_bytes().unwrap();
+        assert!(bytes.starts_with(b"SIP/2.0 486 Busy Here\r\n"));
+
+        let sip = Message::parse(&bytes).unwrap().sip();
+        assert_eq!(sip.status().unwrap().status(), 486);
+        assert_eq!(sip.payload().as_utf8_lossy(), "");
+    }
+
+    #[test]
+    fn test_build_without_mandatory_headers() {
+        let mut message = Message::request("OPTIONS", "sip:bob@example.com").unwrap();
+        assert!(message.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_edit_headers() {
+        let mut message = message_request();
+        message.replace_header("CSeq", "2 MESSAGE").unwrap();
+        message
+            .add_header("Via", "SIP/2.0/TCP 192.0.2.2;branch=z9hG4bKnashds8")
+            .unwrap();
+        message.add_header("X-Other", "two").unwrap();
+        assert!(message.remove_header("x-test").unwrap());
+        assert!(!message.remove_header("X-Test").unwrap());
+        assert!(!message.remove_header("Subject").unwrap());
+
+        let bytes = message.to_bytes().unwrap();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(!text.contains("X-Test"));
+        assert!(text.contains("X-Other: two\r\n"));
+        let sip = Message::parse(&bytes).unwrap().sip();
+        assert_eq!(sip.cseq().seq(), 2);
+        assert_eq!(sip.via().len(), 2);
+
+        /* compact form, a message without Via is refused by the parser */
+        assert!(message.remove_header("v").unwrap());
+        let bytes = message.to_bytes().unwrap();
+        assert!(Message::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_edit_body() {
+        let mut message = message_request();
+        message.set_body("text/plain", b"first").unwrap();
+        message.set_body("application/json", b"{}").unwrap();
+        let sip = Message::parse(&message.to_bytes().unwrap()).unwrap().sip();
+        assert_eq!(sip.content_type().to_string(), "application/json");
+        assert_eq!(sip.payload().as_utf8_lossy(), "{}");
+
+        message.remove_body().unwrap();
+        let bytes = message.to_bytes().unwrap();
+        assert!(bytes.ends_with(b"Content-Length: 0\r\n\r\n"));
+    }
+
+    /* one header per line in the form printed by sofia-sip */
+    const CANONICAL_MESSAGE: &[u8] = b"MESSAGE sip:bob@example.com SIP/2.0\r\n\
+        Via: SIP/2.0/UDP 192.0.2.1;branch=z9hG4bK776asdhds\r\n\
+        Max-Forwards: 70\r\n\
+        From: <sip:alice@example.com>;tag=1928301774\r\n\
+        To: <sip:bob@example.com>\r\n\
+        Call-ID: a84b4c76e66710@pc33.example.com\r\n\
+        CSeq: 1 MESSAGE\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Length: 5\r\n\
+        \r\n\
+        hello";
+
+    #[test]
+    fn test_round_trip() {
+        let mut message = Message::parse(CANONICAL_MESSAGE).unwrap();
+        assert_eq!(message.to_bytes().unwrap(), CANONICAL_MESSAGE);
+
+        /* the torture messages are not in that form (folded lines, compact
+        names, unusual whitespace): their bytes may be normalized by
+        sofia-sip, only the decoded values must survive */
+        for data in &[
+            &torture!("wsinv")[..],
+            &torture!("esc01")[..],
+            &torture!("unreason")[..],
+        ] {
+            let mut first = Message::parse(data).unwrap();
+            let bytes = first.to_bytes().unwrap();
+            let mut second = Message::parse(&bytes).unwrap();
+            assert_eq!(second.to_bytes().unwrap(), bytes);
+
+            let (a, b) = (first.sip(), second.sip());
+            assert_eq!(a.call_id().id(), b.call_id().id());
+            assert_eq!(a.cseq().to_string(), b.cseq().to_string());
+            assert_eq!(a.via().len(), b.via().len());
+            assert_eq!(a.payload().as_utf8_lossy(), b.payload().as_utf8_lossy());
+        }
+    }
+
+    #[test]
+    fn test_parse_trailing_data() {
+        let message = Message::parse(CANONICAL_MESSAGE).unwrap();
+        assert_eq!(message.trailing_len(), 0);
+
+        /* two messages in a single datagram */
+        let data = [CANONICAL_MESSAGE, CANONICAL_MESSAGE].concat();
+        let mut message = Message::parse(&data).unwrap();
+        assert_eq!(message.trailing_len(), CANONICAL_MESSAGE.len());
+        assert_eq!(message.sip().payload().as_utf8_lossy(), "hello");
+        assert_eq!(message.to_bytes().unwrap(), CANONICAL_MESSAGE);
+    }
+
+    #[test]
+    fn test_unknown_headers() {
+        let sip = Message::parse(torture!("wsinv")).unwrap().sip();
+        let names: Vec<&str> = sip.unknown_headers().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec!["NewFangledHeader", "UnknownHeaderWithUnusualValue"]
+        );
+
+        let mut message = message_request();
+        message.add_header("X-Account-Id", "42").unwrap();
+        let sip = Message::parse(&message.to_bytes().unwrap()).unwrap().sip();
+        assert_eq!(sip.unknown_header("x-test"), Some("one"));
+        assert_eq!(sip.unknown_header("X-Account-Id"), Some("42"));
+        assert_eq!(sip.unknown_header("X-Missing"), None);
+    }
+
+    #[test]
+    fn test_sip_ref() {
+        let message = Message::parse(torture!("wsinv")).unwrap();
+        let sip = message.sip_ref();
+        assert_eq!(sip.method().unwrap(), "INVITE");
+        assert_eq!(sip.call_id().id(), "wsinv.ndaksdj@192.0.2.1");
+        assert_eq!(sip.max_forwards().count(), Some(68));
+        assert_eq!(sip.content_type().unwrap(), "application/sdp");
+        assert_eq!(sip.payload().len(), 150);
+        assert_eq!(sip.via().len(), 3);
+        assert_eq!(
+            sip.unknown_header("newfangledheader").unwrap(),
+            sip.to_sip().unknown_header("NewFangledHeader").unwrap()
+        );
+
+        let owned = sip.to_sip();
+        assert_eq!(owned.call_id().id(), "wsinv.ndaksdj@192.0.2.1");
+        assert_eq!(owned.cseq().to_string(), sip.cseq().to_string());
+        assert_eq!(owned.payload().as_utf8_lossy().as_bytes(), sip.payload());
+    }
+
+    #[test]
+    fn test_sip_ref_null() {
+        let sip = unsafe { SipRef::_from_sys(std::ptr::null()) };
+        assert!(sip.method().is_none());
+        assert!(sip.call_id().id().is_empty());
+        assert!(sip.payload().is_empty());
+        assert!(sip.via().is_empty());
+        assert_eq!(sip.unknown_headers().count(), 0);
     }
 }
@@ -1,4 +1,8 @@
+use crate::sip::SubscriptionState;
 use crate::sys;
+use crate::tag::tag::Contact;
+use crate::tag::tag::OutboundOption;
+use crate::tag::tag::SessionRefresher;
 use crate::tag::tag::Tag;
 use crate::tag::tag::TagItem;
 
@@ -46,6 +50,136 @@ impl Builder {
         self.tag(Tag::SipToStr(s.to_string()))
     }
 
+    pub fn nutag_registrar(self, s: &str) -> Self {
+        self.tag(Tag::NuRegistrar(s.to_string()))
+    }
+
+    pub fn nutag_outbound(self, s: &str) -> Self {
+        self.tag(Tag::NuOutbound(s.to_string()))
+    }
+
+    pub fn nutag_outbound_options(self, options: &[OutboundOption]) -> Self {
+        let options: Vec<String> = options.iter().map(|option| option._to_str()).collect();
+        self.nutag_outbound(&options.join(", "))
+    }
+
+    pub fn nutag_m_params(self, s: &str) -> Self {
+        self.tag(Tag::NuMParams(s.to_string()))
+    }
+
+    pub fn nutag_m_features(self, s: &str) -> Self {
+        self.tag(Tag::NuMFeatures(s.to_string()))
+    }
+
+    pub fn nutag_allow(self, s: &str) -> Self {
+        self.tag(Tag::NuAllow(s.to_string()))
+    }
+
+    pub fn siptag_contact_str(self, s: &str) -> Self {
+        self.tag(Tag::SipContactStr(s.to_string()))
+    }
+
+    pub fn siptag_contact(self, contact: &Contact) -> Self {
+        let contact: String = contact.into();
+        self.siptag_contact_str(&contact)
+    }
+
+    pub fn siptag_expires_str(self, s: &str) -> Self {
+        self.tag(Tag::SipExpiresStr(s.to_string()))
+    }
+
+    pub fn siptag_expires(self, seconds: u32) -> Self {
+        self.siptag_expires_str(&seconds.to_string())
+    }
+
+    pub fn siptag_event_str(self, s: &str) -> Self {
+        self.tag(Tag::SipEventStr(s.to_string()))
+    }
+
+    pub fn siptag_accept_str(self, s: &str) -> Self {
+        self.tag(Tag::SipAcceptStr(s.to_string()))
+    }
+
+    pub fn nutag_substate(self, state: SubscriptionState) -> Self {
+        self.tag(Tag::NuSubstate(state))
+    }
+
+    pub fn siptag_etag_str(self, s: &str) -> Self {
+        self.tag(Tag::SipEtagStr(s.to_string()))
+    }
+
+    pub fn siptag_if_match_str(self, s: &str) -> Self {
+        self.tag(Tag::SipIfMatchStr(s.to_string()))
+    }
+
+    pub fn nutag_allow_events(self, s: &str) -> Self {
+        self.tag(Tag::NuAllowEvents(s.to_string()))
+    }
+
+    pub fn siptag_refer_to_str(self, s: &str) -> Self {
+        self.tag(Tag::SipReferToStr(s.to_string()))
+    }
+
+    pub fn siptag_replaces_str(self, s: &str) -> Self {
+        self.tag(Tag::SipReplacesStr(s.to_string()))
+    }
+
+    pub fn siptag_supported_str(self, s: &str) -> Self {
+        self.tag(Tag::SipSupportedStr(s.to_string()))
+    }
+
+    pub fn siptag_require_str(self, s: &str) -> Self {
+        self.tag(Tag::SipRequireStr(s.to_string()))
+    }
+
+    pub fn nutag_early_media(self, enabled: bool) -> Self {
+        self.tag(Tag::NuEarlyMedia(enabled))
+    }
+
+    pub fn nutag_autoprack(self, enabled: bool) -> Self {
+        self.tag(Tag::NuAutoprack(enabled))
+    }
+
+    pub fn nutag_session_timer(self, seconds: u32) -> Self {
+        self.tag(Tag::NuSessionTimer(seconds))
+    }
+
+    pub fn nutag_min_se(self, seconds: u32) -> Self {
+        self.tag(Tag::NuMinSe(seconds))
+    }
+
+    pub fn nutag_session_refresher(self, refresher: SessionRefresher) -> Self {
+        self.tag(Tag::NuSessionRefresher(refresher))
+    }
+
+    pub fn nutag_update_refresh(self, enabled: bool) -> Self {
+        self.tag(Tag::NuUpdateRefresh(enabled))
+    }
+
+    pub fn nutag_method(self, s: &str) -> Self {
+        self.tag(Tag::NuMethod(s.to_string()))
+    }
+
+    pub fn nutag_appl_method(self, s: &str) -> Self {
+        self.tag(Tag::NuApplMethod(s.to_string()))
+    }
+
+    pub fn siptag_www_authenticate_str(self, s: &str) -> Self {
+        self.tag(Tag::SipWwwAuthenticateStr(s.to_string()))
+    }
+
+    pub fn siptag_proxy_authenticate_str(self, s: &str) -> Self {
+        self.tag(Tag::SipProxyAuthenticateStr(s.to_string()))
+    }
+
+    pub fn nutag_auth(self, s: &str) -> Self {
+        self.tag(Tag::NuAuth(s.to_string()))
+    }
+
+    pub fn siptag_header_str(self, s: &str) -> Self {
+        self.tag(Tag::SipHeaderStr(s.to_string()))
+    }
+
     pub fn tag(mut self, tag: Tag) -> Self {
         self.tags.push(tag);
         self
@@ -160,6 +294,249 @@ mod test {
         assert_eq!(res[0], Tag::SipToStr("900@localhost".to_string()));
     }
 
+    #[test]
+    fn test_builder_nutag_registrar() {
+        let res = Builder::default()
+            .nutag_registrar("sip:127.0.0.1:5081")
+            .collect();
+        assert_eq!(res[0], Tag::NuRegistrar("sip:127.0.0.1:5081".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_outbound() {
+        let res = Builder::default().nutag_outbound("natify").collect();
+        assert_eq!(res[0], Tag::NuOutbound("natify".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_outbound_options() {
+        let res = Builder::default()
+            .nutag_outbound_options(&[
+                OutboundOption::OptionsKeepalive(false),
+                OutboundOption::Validate(false),
+                OutboundOption::UseRport(true),
+            ])
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::NuOutbound("no-options-keepalive, no-validate, use-rport".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_nutag_m_params() {
+        let res = Builder::default().nutag_m_params("user=phone").collect();
+        assert_eq!(res[0], Tag::NuMParams("user=phone".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_m_features() {
+        let res = Builder::default().nutag_m_features("expires=60").collect();
+        assert_eq!(res[0], Tag::NuMFeatures("expires=60".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_allow() {
+        let res = Builder::default().nutag_allow("REGISTER").collect();
+        assert_eq!(res[0], Tag::NuAllow("REGISTER".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_contact_str() {
+        let res = Builder::default()
+            .siptag_contact_str("<sip:alice@127.0.0.1:5080>")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::SipContactStr("<sip:alice@127.0.0.1:5080>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_siptag_contact() {
+        let contact = Contact::new("sip:alice@127.0.0.1:5080")
+            .display("Alice \"A\"")
+            .param("transport", "tcp")
+            .param("ob", "")
+            .expires(60);
+        let res = Builder::default().siptag_contact(&contact).collect();
+        assert_eq!(
+            res[0],
+            Tag::SipContactStr(
+                "\"Alice \\\"A\\\"\" <sip:alice@127.0.0.1:5080>;transport=tcp;ob;expires=60"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_builder_siptag_expires_str() {
+        let res = Builder::default().siptag_expires_str("3600").collect();
+        assert_eq!(res[0], Tag::SipExpiresStr("3600".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_expires() {
+        let res = Builder::default().siptag_expires(600).collect();
+        assert_eq!(res[0], Tag::SipExpiresStr("600".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_event_str() {
+        let res = Builder::default().siptag_event_str("presence").collect();
+        assert_eq!(res[0], Tag::SipEventStr("presence".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_accept_str() {
+        let res = Builder::default()
+            .siptag_accept_str("application/pidf+xml")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::SipAcceptStr("application/pidf+xml".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_nutag_substate() {
+        let res = Builder::default()
+            .nutag_substate(SubscriptionState::Pending)
+            .collect();
+        assert_eq!(res[0], Tag::NuSubstate(SubscriptionState::Pending));
+    }
+
+    #[test]
+    fn test_builder_siptag_etag_str() {
+        let res = Builder::default().siptag_etag_str("dx200xyz").collect();
+        assert_eq!(res[0], Tag::SipEtagStr("dx200xyz".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_if_match_str() {
+        let res = Builder::default().siptag_if_match_str("dx200xyz").collect();
+        assert_eq!(res[0], Tag::SipIfMatchStr("dx200xyz".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_allow_events() {
+        let res = Builder::default().nutag_allow_events("presence").collect();
+        assert_eq!(res[0], Tag::NuAllowEvents("presence".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_refer_to_str() {
+        let res = Builder::default()
+            .siptag_refer_to_str("<sip:bob@127.0.0.1>")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::SipReferToStr("<sip:bob@127.0.0.1>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_siptag_replaces_str() {
+        let res = Builder::default()
+            .siptag_replaces_str("abc;from-tag=1;to-tag=2")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::SipReplacesStr("abc;from-tag=1;to-tag=2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_siptag_supported_str() {
+        let res = Builder::default().siptag_supported_str("100rel").collect();
+        assert_eq!(res[0], Tag::SipSupportedStr("100rel".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_require_str() {
+        let res = Builder::default().siptag_require_str("100rel").collect();
+        assert_eq!(res[0], Tag::SipRequireStr("100rel".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_early_media() {
+        let res = Builder::default().nutag_early_media(true).collect();
+        assert_eq!(res[0], Tag::NuEarlyMedia(true));
+    }
+
+    #[test]
+    fn test_builder_nutag_autoprack() {
+        let res = Builder::default().nutag_autoprack(false).collect();
+        assert_eq!(res[0], Tag::NuAutoprack(false));
+    }
+
+    #[test]
+    fn test_builder_session_timers() {
+        let res = Builder::default()
+            .nutag_session_timer(1800)
+            .nutag_min_se(90)
+            .nutag_session_refresher(SessionRefresher::Local)
+            .nutag_update_refresh(true)
+            .collect();
+        assert_eq!(res[0], Tag::NuSessionTimer(1800));
+        assert_eq!(res[1], Tag::NuMinSe(90));
+        assert_eq!(res[2], Tag::NuSessionRefresher(SessionRefresher::Local));
+        assert_eq!(res[3], Tag::NuUpdateRefresh(true));
+    }
+
+    #[test]
+    fn test_builder_nutag_method() {
+        let res = Builder::default().nutag_method("FOO").collect();
+        assert_eq!(res[0], Tag::NuMethod("FOO".to_string()));
+    }
+
+    #[test]
+    fn test_builder_nutag_appl_method() {
+        let res = Builder::default().nutag_appl_method("FOO").collect();
+        assert_eq!(res[0], Tag::NuApplMethod("FOO".to_string()));
+    }
+
+    #[test]
+    fn test_builder_siptag_www_authenticate_str() {
+        let res = Builder::default()
+            .siptag_www_authenticate_str("Digest realm=\"example.com\", nonce=\"abc\"")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::SipWwwAuthenticateStr("Digest realm=\"example.com\", nonce=\"abc\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_siptag_proxy_authenticate_str() {
+        let res = Builder::default()
+            .siptag_proxy_authenticate_str("Digest realm=\"example.com\", nonce=\"abc\"")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::SipProxyAuthenticateStr("Digest realm=\"example.com\", nonce=\"abc\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_nutag_auth() {
+        let res = Builder::default()
+            .nutag_auth("Digest:\"example.com\":alice:secret")
+            .collect();
+        assert_eq!(
+            res[0],
+            Tag::NuAuth("Digest:\"example.com\":alice:secret".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_siptag_header_str() {
+        let res = Builder::default()
+            .siptag_header_str("X-Account-Id: 42")
+            .collect();
+        assert_eq!(res[0], Tag::SipHeaderStr("X-Account-Id: 42".to_string()));
+    }
+
     #[test]
     fn test_builder_from_sys() {}
 }
@@ -1,4 +1,10 @@
 pub mod builder;
 pub mod tag;
 pub use crate::tag::builder::Builder as TagBuilder;
+pub use crate::tag::tag::CallState;
+pub use crate::tag::tag::Contact;
+pub use crate::tag::tag::NeaSub;
+pub use crate::tag::tag::OutboundOption;
+pub use crate::tag::tag::ReferEvent;
+pub use crate::tag::tag::SessionRefresher;
 pub use crate::tag::tag::Tag;
@@ -1,3 +1,6 @@
+use crate::nua::Handle;
+use crate::sip::SipEvent;
+use crate::sip::SubscriptionState;
 use crate::sys;
 use std::ffi::CStr;
 use std::ffi::CString;
@@ -40,6 +43,267 @@ impl TagType {
     // }
 }
 
+/// Watcher of a notifier, reported with `IncomingSubscription` and passed
+/// back to [`Handle::authorize`](crate::Handle::authorize).
+///
+/// It is only given by the stack, it cannot be built by the application.
+/// It refers to memory of the stack: it is not valid anymore once the
+/// watcher is terminated (see the safety section of `authorize`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeaSub {
+    /* nea_sub_t of the notifier */
+    ptr: sys::tag_value_t,
+}
+
+/// Event header of the implicit subscription of a REFER, reported with
+/// `IncomingRefer` and passed back in the tags of the INVITE of the
+/// referred call (see [`Handle::invite_referred`]).
+///
+/// It is a copy of the header, not the pointer given by the stack: it
+/// remains valid after the callback returns (eg: in queued events).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferEvent {
+    value: String,
+}
+
+impl ReferEvent {
+    /// Value of the header, eg: `refer;id=1`.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// Header given to a tag by pointer, parsed from its value in its own
+/// memory home.
+#[derive(Debug)]
+pub(crate) struct TagHeader {
+    class: *mut sys::msg_hclass_t,
+    value: CString,
+    home: *mut sys::su_home_t,
+    header: *mut sys::msg_header_t,
+}
+
+impl TagHeader {
+    fn make(class: *mut sys::msg_hclass_t, value: CString) -> Self {
+        let home = unsafe { sys::su_home_new(std::mem::size_of::<sys::su_home_t>() as _) }
+            as *mut sys::su_home_t;
+        assert!(!home.is_null());
+        /* null if the value is not a valid header */
+        let header = unsafe { sys::msg_header_make(home, class, value.as_ptr()) };
+        TagHeader {
+            class,
+            value,
+            home,
+            header,
+        }
+    }
+
+    fn refer_event(value: CString) -> Self {
+        Self::make(unsafe { sys::sip_event_class.as_mut_ptr() }, value)
+    }
+}
+
+impl Clone for TagHeader {
+    fn clone(&self) -> Self {
+        Self::make(self.class, self.value.clone())
+    }
+}
+
+impl Drop for TagHeader {
+    fn drop(&mut self) {
+        unsafe { sys::su_home_unref(self.home) };
+    }
+}
+
+/// Which side refreshes the session when session timers (RFC 4028) are enabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionRefresher {
+    /// No preference, let the other side choose.
+    None,
+    Local,
+    Remote,
+    Any,
+}
+
+impl SessionRefresher {
+    pub(crate) fn _from_sys(refresher: u32) -> Self {
+        match refresher {
+            sys::nua_session_refresher_nua_local_refresher => SessionRefresher::Local,
+            sys::nua_session_refresher_nua_remote_refresher => SessionRefresher::Remote,
+            sys::nua_session_refresher_nua_any_refresher => SessionRefresher::Any,
+            _ => SessionRefresher::None,
+        }
+    }
+
+    pub(crate) fn _to_sys(self) -> u32 {
+        match self {
+            SessionRefresher::None => sys::nua_session_refresher_nua_no_refresher,
+            SessionRefresher::Local => sys::nua_session_refresher_nua_local_refresher,
+            SessionRefresher::Remote => sys::nua_session_refresher_nua_remote_refresher,
+            SessionRefresher::Any => sys::nua_session_refresher_nua_any_refresher,
+        }
+    }
+}
+
+/// Contact of a REGISTER, made of its URL and parameters (see
+/// [`Builder::siptag_contact`](crate::TagBuilder::siptag_contact)), eg:
+/// `Contact::new("sip:alice@127.0.0.1:5080").expires(60)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    display: Option<String>,
+    url: String,
+    params: Vec<String>,
+}
+
+impl Contact {
+    pub fn new(url: &str) -> Self {
+        Contact {
+            display: None,
+            url: url.to_string(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Display name, quoted in the header.
+    pub fn display(mut self, display: &str) -> Self {
+        self.display = Some(display.to_string());
+        self
+    }
+
+    /// Header parameter, eg: `param("transport", "tcp")`; an empty `value`
+    /// gives a parameter without value.
+    pub fn param(mut self, name: &str, value: &str) -> Self {
+        if value.is_empty() {
+            self.params.push(name.to_string());
+        } else {
+            self.params.push(format!("{}={}", name, value));
+        }
+        self
+    }
+
+    /// Expiry of this binding in seconds.
+    pub fn expires(self, seconds: u32) -> Self {
+        self.param("expires", &seconds.to_string())
+    }
+}
+
+impl From<&Contact> for String {
+    fn from(value: &Contact) -> String {
+        let mut contact = String::new();
+        if let Some(display) = &value.display {
+            let display = display.replace('\\', "\\\\").replace('"', "\\\"");
+            contact.push_str(&format!("\"{}\" ", display));
+        }
+        contact.push_str(&format!("<{}>", value.url));
+        for param in &value.params {
+            contact.push(';');
+            contact.push_str(param);
+        }
+        contact
+    }
+}
+
+/// Option of the outbound engine of a registration (see
+/// [`Builder::nutag_outbound_options`](crate::TagBuilder::nutag_outbound_options)),
+/// `true` enables it and `false` disables it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutboundOption {
+    /// Use GRUU (RFC 5627) for the Contact.
+    Gruuize(bool),
+    /// Use SIP outbound (RFC 5626).
+    Outbound(bool),
+    /// Find the public address of the binding behind a NAT.
+    Natify(bool),
+    /// Keep the binding alive with OPTIONS.
+    OptionsKeepalive(bool),
+    /// Check the binding with an OPTIONS to itself.
+    Validate(bool),
+    UseRport(bool),
+    UseConnect(bool),
+    UseSocks(bool),
+    UseUpnp(bool),
+    UseStun(bool),
+}
+
+impl OutboundOption {
+    pub(crate) fn _to_str(self) -> String {
+        let (name, enabled) = match self {
+            OutboundOption::Gruuize(v) => ("gruuize", v),
+            OutboundOption::Outbound(v) => ("outbound", v),
+            OutboundOption::Natify(v) => ("natify", v),
+            OutboundOption::OptionsKeepalive(v) => ("options-keepalive", v),
+            OutboundOption::Validate(v) => ("validate", v),
+            OutboundOption::UseRport(v) => ("use-rport", v),
+            OutboundOption::UseConnect(v) => ("use-connect", v),
+            OutboundOption::UseSocks(v) => ("use-socks", v),
+            OutboundOption::UseUpnp(v) => ("use-upnp", v),
+            OutboundOption::UseStun(v) => ("use-stun", v),
+        };
+        if enabled {
+            name.to_string()
+        } else {
+            format!("no-{}", name)
+        }
+    }
+}
+
+/// State of a call (INVITE session), reported by `IncomingState` events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallState {
+    /// Initial state.
+    Init,
+    /// INVITE sent.
+    Calling,
+    /// 18X received.
+    Proceeding,
+    /// 2XX received.
+    Completing,
+    /// INVITE received.
+    Received,
+    /// 18X sent (w/ SDP).
+    Early,
+    /// 2XX sent, waiting for ACK.
+    Completed,
+    /// 2XX and ACK received or sent.
+    Ready,
+    /// BYE sent.
+    Terminating,
+    /// BYE complete.
+    Terminated,
+}
+
+impl CallState {
+    pub(crate) fn _from_sys(callstate: u32) -> Self {
+        match callstate {
+            sys::nua_callstate_nua_callstate_calling => CallState::Calling,
+            sys::nua_callstate_nua_callstate_proceeding => CallState::Proceeding,
+            sys::nua_callstate_nua_callstate_completing => CallState::Completing,
+            sys::nua_callstate_nua_callstate_received => CallState::Received,
+            sys::nua_callstate_nua_callstate_early => CallState::Early,
+            sys::nua_callstate_nua_callstate_completed => CallState::Completed,
+            sys::nua_callstate_nua_callstate_ready => CallState::Ready,
+            sys::nua_callstate_nua_callstate_terminating => CallState::Terminating,
+            sys::nua_callstate_nua_callstate_terminated => CallState::Terminated,
+            _ => CallState::Init,
+        }
+    }
+
+    pub(crate) fn _to_sys(self) -> u32 {
+        match self {
+            CallState::Init => sys::nua_callstate_nua_callstate_init,
+            CallState::Calling => sys::nua_callstate_nua_callstate_calling,
+            CallState::Proceeding => sys::nua_callstate_nua_callstate_proceeding,
+            CallState::Completing => sys::nua_callstate_nua_callstate_completing,
+            CallState::Received => sys::nua_callstate_nua_callstate_received,
+            CallState::Early => sys::nua_callstate_nua_callstate_early,
+            CallState::Completed => sys::nua_callstate_nua_callstate_completed,
+            CallState::Ready => sys::nua_callstate_nua_callstate_ready,
+            CallState::Terminating => sys::nua_callstate_nua_callstate_terminating,
+            CallState::Terminated => sys::nua_callstate_nua_callstate_terminated,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum TagItem {
     _PlaceHolder(CString),
@@ -51,6 +315,43 @@ pub(crate) enum TagItem {
     SipContentTypeStr(CString),
     SipPayloadStr(CString),
     SipToStr(CString),
+    NuRegistrar(CString),
+    NuOutbound(CString),
+    NuMParams(CString),
+    NuMFeatures(CString),
+    NuAllow(CString),
+    SipContactStr(CString),
+    SipExpiresStr(CString),
+    SipEventStr(CString),
+    SipAcceptStr(CString),
+    NuSubstate(i32),
+    NeaSub(sys::tag_value_t),
+    NuReferEvent(TagHeader),
+    NuNotifyRefer(Handle),
+    NuEarlyMedia(bool),
+    NuAutoprack(bool),
+    NuSessionTimer(u32),
+    NuMinSe(u32),
+    NuSessionRefresher(u32),
+    NuUpdateRefresh(bool),
+    SipEtagStr(CString),
+    SipIfMatchStr(CString),
+    NuAllowEvents(CString),
+    SipReferToStr(CString),
+    SipReplacesStr(CString),
+    SipSupportedStr(CString),
+    SipRequireStr(CString),
+    NuMethod(CString),
+    NuApplMethod(CString),
+    SipWwwAuthenticateStr(CString),
+    SipProxyAuthenticateStr(CString),
+    NuAuth(CString),
+    NuCallstate(u32),
+    NuOfferSent(bool),
+    NuAnswerSent(bool),
+    NuOfferRecv(bool),
+    NuAnswerRecv(bool),
+    SipHeaderStr(CString),
     NotImplemented(TagType),
     Null,
     End,
@@ -69,6 +370,47 @@ impl TagItem {
             TagItem::SipContentTypeStr(_) => unsafe { sys::siptag_content_type_str.as_ptr() },
             TagItem::SipPayloadStr(_) => unsafe { sys::siptag_payload_str.as_ptr() },
             TagItem::SipToStr(_) => unsafe { sys::siptag_to_str.as_ptr() },
+            TagItem::NuRegistrar(_) => unsafe { sys::nutag_registrar.as_ptr() },
+            TagItem::NuOutbound(_) => unsafe { sys::nutag_outbound.as_ptr() },
+            TagItem::NuMParams(_) => unsafe { sys::nutag_m_params.as_ptr() },
+            TagItem::NuMFeatures(_) => unsafe { sys::nutag_m_features.as_ptr() },
+            TagItem::NuAllow(_) => unsafe { sys::nutag_allow.as_ptr() },
+            TagItem::SipContactStr(_) => unsafe { sys::siptag_contact_str.as_ptr() },
+            TagItem::SipExpiresStr(_) => unsafe { sys::siptag_expires_str.as_ptr() },
+            TagItem::SipEventStr(_) => unsafe { sys::siptag_event_str.as_ptr() },
+            TagItem::SipAcceptStr(_) => unsafe { sys::siptag_accept_str.as_ptr() },
+            TagItem::NuSubstate(_) => unsafe { sys::nutag_substate.as_ptr() },
+            TagItem::NeaSub(_) => unsafe { sys::neatag_sub.as_ptr() },
+            TagItem::NuReferEvent(_) => unsafe { sys::nutag_refer_event.as_ptr() },
+            TagItem::NuNotifyRefer(_) => unsafe { sys::nutag_notify_refer.as_ptr() },
+            TagItem::NuEarlyMedia(_) => unsafe { sys::nutag_early_media.as_ptr() },
+            TagItem::NuAutoprack(_) => unsafe { sys::nutag_autoprack.as_ptr() },
+            TagItem::NuSessionTimer(_) => unsafe { sys::nutag_session_timer.as_ptr() },
+            TagItem::NuMinSe(_) => unsafe { sys::nutag_min_se.as_ptr() },
+            TagItem::NuSessionRefresher(_) => unsafe { sys::nutag_session_refresher.as_ptr() },
+            TagItem::NuCallstate(_) => unsafe { sys::nutag_callstate.as_ptr() },
+            TagItem::NuOfferSent(_) => unsafe { sys::nutag_offer_sent.as_ptr() },
+            TagItem::NuAnswerSent(_) => unsafe { sys::nutag_answer_sent.as_ptr() },
+            TagItem::NuOfferRecv(_) => unsafe { sys::nutag_offer_recv.as_ptr() },
+            TagItem::NuAnswerRecv(_) => unsafe { sys::nutag_answer_recv.as_ptr() },
+            TagItem::NuUpdateRefresh(_) => unsafe { sys::nutag_update_refresh.as_ptr() },
+            TagItem::SipEtagStr(_) => unsafe { sys::siptag_etag_str.as_ptr() },
+            TagItem::SipIfMatchStr(_) => unsafe { sys::siptag_if_match_str.as_ptr() },
+            TagItem::NuAllowEvents(_) => unsafe { sys::nutag_allow_events.as_ptr() },
+            TagItem::SipReferToStr(_) => unsafe { sys::siptag_refer_to_str.as_ptr() },
+            TagItem::SipReplacesStr(_) => unsafe { sys::siptag_replaces_str.as_ptr() },
+            TagItem::SipSupportedStr(_) => unsafe { sys::siptag_supported_str.as_ptr() },
+            TagItem::SipRequireStr(_) => unsafe { sys::siptag_require_str.as_ptr() },
+            TagItem::NuMethod(_) => unsafe { sys::nutag_method.as_ptr() },
+            TagItem::NuApplMethod(_) => unsafe { sys::nutag_appl_method.as_ptr() },
+            TagItem::SipWwwAuthenticateStr(_) => unsafe {
+                sys::siptag_www_authenticate_str.as_ptr()
+            },
+            TagItem::SipProxyAuthenticateStr(_) => unsafe {
+                sys::siptag_proxy_authenticate_str.as_ptr()
+            },
+            TagItem::NuAuth(_) => unsafe { sys::nutag_auth.as_ptr() },
+            TagItem::SipHeaderStr(_) => unsafe { sys::siptag_header_str.as_ptr() },
             TagItem::Null | TagItem::End => std::ptr::null() as sys::tag_type_t,
         }
     }
@@ -82,7 +424,45 @@ impl TagItem {
             | TagItem::SipSubjectStr(cstring)
             | TagItem::SipContentTypeStr(cstring)
             | TagItem::SipPayloadStr(cstring)
-            | TagItem::SipToStr(cstring) => cstring.as_ptr() as sys::tag_value_t,
+            | TagItem::SipToStr(cstring)
+            | TagItem::NuRegistrar(cstring)
+            | TagItem::NuOutbound(cstring)
+            | TagItem::NuMParams(cstring)
+            | TagItem::NuMFeatures(cstring)
+            | TagItem::NuAllow(cstring)
+            | TagItem::SipContactStr(cstring)
+            | TagItem::SipExpiresStr(cstring)
+            | TagItem::SipEventStr(cstring)
+            | TagItem::SipAcceptStr(cstring)
+            | TagItem::SipEtagStr(cstring)
+            | TagItem::SipIfMatchStr(cstring)
+            | TagItem::NuAllowEvents(cstring)
+            | TagItem::SipReferToStr(cstring)
+            | TagItem::SipReplacesStr(cstring)
+            | TagItem::SipSupportedStr(cstring)
+            | TagItem::SipRequireStr(cstring)
+            | TagItem::NuMethod(cstring)
+            | TagItem::NuApplMethod(cstring)
+            | TagItem::SipWwwAuthenticateStr(cstring)
+            | TagItem::SipProxyAuthenticateStr(cstring)
+            | TagItem::NuAuth(cstring)
+            | TagItem::SipHeaderStr(cstring) => cstring.as_ptr() as sys::tag_value_t,
+            TagItem::NuSubstate(v) => *v as sys::tag_value_t,
+            TagItem::NeaSub(v) => *v,
+            TagItem::NuReferEvent(v) => v.header as sys::tag_value_t,
+            /* null once the handle is destroyed, sofia ignores it */
+            TagItem::NuNotifyRefer(v) => v.c_ptr() as sys::tag_value_t,
+            TagItem::NuEarlyMedia(v) | TagItem::NuAutoprack(v) | TagItem::NuUpdateRefresh(v) => {
+                *v as sys::tag_value_t
+            }
+            TagItem::NuSessionTimer(v) | TagItem::NuMinSe(v) | TagItem::NuSessionRefresher(v) => {
+                *v as sys::tag_value_t
+            }
+            TagItem::NuCallstate(v) => *v as sys::tag_value_t,
+            TagItem::NuOfferSent(v)
+            | TagItem::NuAnswerSent(v)
+            | TagItem::NuOfferRecv(v)
+            | TagItem::NuAnswerRecv(v) => *v as sys::tag_value_t,
             TagItem::NotImplemented(_) | TagItem::Null | TagItem::End => 0 as sys::tag_value_t,
         }
     }
@@ -165,6 +545,103 @@ impl TagItem {
             } else if tag_type == sys::siptag_to_str.as_ptr() {
                 let v = Self::_convert_t_value_to_cstring(tag_value);
                 Self::SipToStr(v)
+            } else if tag_type == sys::nutag_registrar.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuRegistrar(v)
+            } else if tag_type == sys::nutag_outbound.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuOutbound(v)
+            } else if tag_type == sys::nutag_m_params.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuMParams(v)
+            } else if tag_type == sys::nutag_m_features.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuMFeatures(v)
+            } else if tag_type == sys::nutag_allow.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuAllow(v)
+            } else if tag_type == sys::siptag_contact_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipContactStr(v)
+            } else if tag_type == sys::siptag_expires_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipExpiresStr(v)
+            } else if tag_type == sys::siptag_event_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipEventStr(v)
+            } else if tag_type == sys::siptag_accept_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipAcceptStr(v)
+            } else if tag_type == sys::nutag_substate.as_ptr() {
+                Self::NuSubstate(tag_value as i32)
+            } else if tag_type == sys::neatag_sub.as_ptr() {
+                Self::NeaSub(tag_value)
+            } else if tag_type == sys::nutag_refer_event.as_ptr() {
+                /* copied: the header belongs to the message of the event */
+                let event = SipEvent::_from_sys(tag_value as *const sys::sip_event_t);
+                let value: String = (&event).into();
+                Self::NuReferEvent(TagHeader::refer_event(string_to_cstring_lossy(&value)))
+            } else if tag_type == sys::nutag_early_media.as_ptr() {
+                Self::NuEarlyMedia(tag_value != 0)
+            } else if tag_type == sys::nutag_autoprack.as_ptr() {
+                Self::NuAutoprack(tag_value != 0)
+            } else if tag_type == sys::nutag_session_timer.as_ptr() {
+                Self::NuSessionTimer(tag_value as u32)
+            } else if tag_type == sys::nutag_min_se.as_ptr() {
+                Self::NuMinSe(tag_value as u32)
+            } else if tag_type == sys::nutag_session_refresher.as_ptr() {
+                Self::NuSessionRefresher(tag_value as u32)
+            } else if tag_type == sys::nutag_update_refresh.as_ptr() {
+                Self::NuUpdateRefresh(tag_value != 0)
+            } else if tag_type == sys::nutag_callstate.as_ptr() {
+                Self::NuCallstate(tag_value as u32)
+            } else if tag_type == sys::nutag_offer_sent.as_ptr() {
+                Self::NuOfferSent(tag_value != 0)
+            } else if tag_type == sys::nutag_answer_sent.as_ptr() {
+                Self::NuAnswerSent(tag_value != 0)
+            } else if tag_type == sys::nutag_offer_recv.as_ptr() {
+                Self::NuOfferRecv(tag_value != 0)
+            } else if tag_type == sys::nutag_answer_recv.as_ptr() {
+                Self::NuAnswerRecv(tag_value != 0)
+            } else if tag_type == sys::siptag_etag_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipEtagStr(v)
+            } else if tag_type == sys::siptag_if_match_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipIfMatchStr(v)
+            } else if tag_type == sys::nutag_allow_events.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuAllowEvents(v)
+            } else if tag_type == sys::siptag_refer_to_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipReferToStr(v)
+            } else if tag_type == sys::siptag_replaces_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipReplacesStr(v)
+            } else if tag_type == sys::siptag_supported_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipSupportedStr(v)
+            } else if tag_type == sys::siptag_require_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipRequireStr(v)
+            } else if tag_type == sys::nutag_method.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuMethod(v)
+            } else if tag_type == sys::nutag_appl_method.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuApplMethod(v)
+            } else if tag_type == sys::siptag_www_authenticate_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipWwwAuthenticateStr(v)
+            } else if tag_type == sys::siptag_proxy_authenticate_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipProxyAuthenticateStr(v)
+            } else if tag_type == sys::nutag_auth.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::NuAuth(v)
+            } else if tag_type == sys::siptag_header_str.as_ptr() {
+                let v = Self::_convert_t_value_to_cstring(tag_value);
+                Self::SipHeaderStr(v)
             } else {
                 let tag_type = TagType::_from_sys(tagi.t_tag);
                 Self::NotImplemented(tag_type)
@@ -197,6 +674,48 @@ impl From<&Tag> for TagItem {
             Tag::SipContentTypeStr(v) => TagItem::SipContentTypeStr(string_to_cstring_lossy(v)),
             Tag::SipPayloadStr(v) => TagItem::SipPayloadStr(string_to_cstring_lossy(v)),
             Tag::SipToStr(v) => TagItem::SipToStr(string_to_cstring_lossy(v)),
+            Tag::NuRegistrar(v) => TagItem::NuRegistrar(string_to_cstring_lossy(v)),
+            Tag::NuOutbound(v) => TagItem::NuOutbound(string_to_cstring_lossy(v)),
+            Tag::NuMParams(v) => TagItem::NuMParams(string_to_cstring_lossy(v)),
+            Tag::NuMFeatures(v) => TagItem::NuMFeatures(string_to_cstring_lossy(v)),
+            Tag::NuAllow(v) => TagItem::NuAllow(string_to_cstring_lossy(v)),
+            Tag::SipContactStr(v) => TagItem::SipContactStr(string_to_cstring_lossy(v)),
+            Tag::SipExpiresStr(v) => TagItem::SipExpiresStr(string_to_cstring_lossy(v)),
+            Tag::SipEventStr(v) => TagItem::SipEventStr(string_to_cstring_lossy(v)),
+            Tag::SipAcceptStr(v) => TagItem::SipAcceptStr(string_to_cstring_lossy(v)),
+            Tag::NuSubstate(v) => TagItem::NuSubstate(v._to_sys()),
+            Tag::NeaSub(v) => TagItem::NeaSub(v.ptr),
+            Tag::NuReferEvent(v) => {
+                TagItem::NuReferEvent(TagHeader::refer_event(string_to_cstring_lossy(&v.value)))
+            }
+            Tag::NuEarlyMedia(v) => TagItem::NuEarlyMedia(*v),
+            Tag::NuAutoprack(v) => TagItem::NuAutoprack(*v),
+            Tag::NuSessionTimer(v) => TagItem::NuSessionTimer(*v),
+            Tag::NuMinSe(v) => TagItem::NuMinSe(*v),
+            Tag::NuSessionRefresher(v) => TagItem::NuSessionRefresher(v._to_sys()),
+            Tag::NuCallstate(v) => TagItem::NuCallstate(v._to_sys()),
+            Tag::NuOfferSent(v) => TagItem::NuOfferSent(*v),
+            Tag::NuAnswerSent(v) => TagItem::NuAnswerSent(*v),
+            Tag::NuOfferRecv(v) => TagItem::NuOfferRecv(*v),
+            Tag::NuAnswerRecv(v) => TagItem::NuAnswerRecv(*v),
+            Tag::NuUpdateRefresh(v) => TagItem::NuUpdateRefresh(*v),
+            Tag::SipEtagStr(v) => TagItem::SipEtagStr(string_to_cstring_lossy(v)),
+            Tag::SipIfMatchStr(v) => TagItem::SipIfMatchStr(string_to_cstring_lossy(v)),
+            Tag::NuAllowEvents(v) => TagItem::NuAllowEvents(string_to_cstring_lossy(v)),
+            Tag::SipReferToStr(v) => TagItem::SipReferToStr(string_to_cstring_lossy(v)),
+            Tag::SipReplacesStr(v) => TagItem::SipReplacesStr(string_to_cstring_lossy(v)),
+            Tag::SipSupportedStr(v) => TagItem::SipSupportedStr(string_to_cstring_lossy(v)),
+            Tag::SipRequireStr(v) => TagItem::SipRequireStr(string_to_cstring_lossy(v)),
+            Tag::NuMethod(v) => TagItem::NuMethod(string_to_cstring_lossy(v)),
+            Tag::NuApplMethod(v) => TagItem::NuApplMethod(string_to_cstring_lossy(v)),
+            Tag::SipWwwAuthenticateStr(v) => {
+                TagItem::SipWwwAuthenticateStr(string_to_cstring_lossy(v))
+            }
+            Tag::SipProxyAuthenticateStr(v) => {
+                TagItem::SipProxyAuthenticateStr(string_to_cstring_lossy(v))
+            }
+            Tag::NuAuth(v) => TagItem::NuAuth(string_to_cstring_lossy(v)),
+            Tag::SipHeaderStr(v) => TagItem::SipHeaderStr(string_to_cstring_lossy(v)),
             Tag::NotImplemented(v) => TagItem::NotImplemented(v.clone()),
             Tag::Null => Self::Null,
             Tag::End => Self::End,
@@ -234,6 +753,48 @@ pub enum Tag {
     SipContentTypeStr(String),
     SipPayloadStr(String),
     SipToStr(String),
+    NuRegistrar(String),
+    NuOutbound(String),
+    NuMParams(String),
+    NuMFeatures(String),
+    NuAllow(String),
+    SipContactStr(String),
+    SipExpiresStr(String),
+    SipEventStr(String),
+    SipAcceptStr(String),
+    NuSubstate(SubscriptionState),
+    NeaSub(NeaSub),
+    NuReferEvent(ReferEvent),
+    NuEarlyMedia(bool),
+    NuAutoprack(bool),
+    /// Session-Expires in seconds, 0 disables session timers.
+    NuSessionTimer(u32),
+    /// Min-SE in seconds.
+    NuMinSe(u32),
+    NuSessionRefresher(SessionRefresher),
+    NuUpdateRefresh(bool),
+    SipEtagStr(String),
+    SipIfMatchStr(String),
+    NuAllowEvents(String),
+    SipReferToStr(String),
+    SipReplacesStr(String),
+    SipSupportedStr(String),
+    SipRequireStr(String),
+    NuMethod(String),
+    NuApplMethod(String),
+    SipWwwAuthenticateStr(String),
+    SipProxyAuthenticateStr(String),
+    NuAuth(String),
+    NuCallstate(CallState),
+    /// An SDP offer was sent, reported by `IncomingState`.
+    NuOfferSent(bool),
+    /// An SDP answer was sent, reported by `IncomingState`.
+    NuAnswerSent(bool),
+    /// An SDP offer was received, reported by `IncomingState`.
+    NuOfferRecv(bool),
+    /// An SDP answer was received, reported by `IncomingState`.
+    NuAnswerRecv(bool),
+    SipHeaderStr(String),
     NotImplemented(TagType),
     Null,
     End,
@@ -253,6 +814,52 @@ impl From<&TagItem> for Tag {
             }
             TagItem::SipPayloadStr(v) => Tag::SipPayloadStr(v.to_string_lossy().into_owned()),
             TagItem::SipToStr(v) => Tag::SipToStr(v.to_string_lossy().into_owned()),
+            TagItem::NuRegistrar(v) => Tag::NuRegistrar(v.to_string_lossy().into_owned()),
+            TagItem::NuOutbound(v) => Tag::NuOutbound(v.to_string_lossy().into_owned()),
+            TagItem::NuMParams(v) => Tag::NuMParams(v.to_string_lossy().into_owned()),
+            TagItem::NuMFeatures(v) => Tag::NuMFeatures(v.to_string_lossy().into_owned()),
+            TagItem::NuAllow(v) => Tag::NuAllow(v.to_string_lossy().into_owned()),
+            TagItem::SipContactStr(v) => Tag::SipContactStr(v.to_string_lossy().into_owned()),
+            TagItem::SipExpiresStr(v) => Tag::SipExpiresStr(v.to_string_lossy().into_owned()),
+            TagItem::SipEventStr(v) => Tag::SipEventStr(v.to_string_lossy().into_owned()),
+            TagItem::SipAcceptStr(v) => Tag::SipAcceptStr(v.to_string_lossy().into_owned()),
+            TagItem::NuSubstate(v) => Tag::NuSubstate(SubscriptionState::_from_sys(*v)),
+            TagItem::NeaSub(v) => Tag::NeaSub(NeaSub { ptr: *v }),
+            TagItem::NuReferEvent(v) => Tag::NuReferEvent(ReferEvent {
+                value: v.value.to_string_lossy().into_owned(),
+            }),
+            /* only given by `Handle::invite_referred` */
+            TagItem::NuNotifyRefer(_) => Tag::NotImplemented(TagType::_from_sys(tag.symbol())),
+            TagItem::NuEarlyMedia(v) => Tag::NuEarlyMedia(*v),
+            TagItem::NuAutoprack(v) => Tag::NuAutoprack(*v),
+            TagItem::NuSessionTimer(v) => Tag::NuSessionTimer(*v),
+            TagItem::NuMinSe(v) => Tag::NuMinSe(*v),
+            TagItem::NuSessionRefresher(v) => {
+                Tag::NuSessionRefresher(SessionRefresher::_from_sys(*v))
+            }
+            TagItem::NuCallstate(v) => Tag::NuCallstate(CallState::_from_sys(*v)),
+            TagItem::NuOfferSent(v) => Tag::NuOfferSent(*v),
+            TagItem::NuAnswerSent(v) => Tag::NuAnswerSent(*v),
+            TagItem::NuOfferRecv(v) => Tag::NuOfferRecv(*v),
+            TagItem::NuAnswerRecv(v) => Tag::NuAnswerRecv(*v),
+            TagItem::NuUpdateRefresh(v) => Tag::NuUpdateRefresh(*v),
+            TagItem::SipEtagStr(v) => Tag::SipEtagStr(v.to_string_lossy().into_owned()),
+            TagItem::SipIfMatchStr(v) => Tag::SipIfMatchStr(v.to_string_lossy().into_owned()),
+            TagItem::NuAllowEvents(v) => Tag::NuAllowEvents(v.to_string_lossy().into_owned()),
+            TagItem::SipReferToStr(v) => Tag::SipReferToStr(v.to_string_lossy().into_owned()),
+            TagItem::SipReplacesStr(v) => Tag::SipReplacesStr(v.to_string_lossy().into_owned()),
+            TagItem::SipSupportedStr(v) => Tag::SipSupportedStr(v.to_string_lossy().into_owned()),
+            TagItem::SipRequireStr(v) => Tag::SipRequireStr(v.to_string_lossy().into_owned()),
+            TagItem::NuMethod(v) => Tag::NuMethod(v.to_string_lossy().into_owned()),
+            TagItem::NuApplMethod(v) => Tag::NuApplMethod(v.to_string_lossy().into_owned()),
+            TagItem::SipWwwAuthenticateStr(v) => {
+                Tag::SipWwwAuthenticateStr(v.to_string_lossy().into_owned())
+            }
+            TagItem::SipProxyAuthenticateStr(v) => {
+                Tag::SipProxyAuthenticateStr(v.to_string_lossy().into_owned())
+            }
+            TagItem::NuAuth(v) => Tag::NuAuth(v.to_string_lossy().into_owned()),
+            TagItem::SipHeaderStr(v) => Tag::SipHeaderStr(v.to_string_lossy().into_owned()),
             TagItem::NotImplemented(v) => Tag::NotImplemented(v.clone()),
             TagItem::Null => Self::Null,
             TagItem::End => Self::End,
@@ -342,6 +949,81 @@ mod tests {
         assert_eq!(tag_item.sofia_string(), "nua::url: <800@localhost>");
     }
 
+    #[test]
+    fn test_convert_nea_sub_tag_item_to_tag_and_back() {
+        /* only the stack gives a watcher: it is kept opaque */
+        let tag: Tag = TagItem::NeaSub(0x1000).into();
+        assert_eq!(tag, Tag::NeaSub(NeaSub { ptr: 0x1000 }));
+        assert_eq!(TagItem::from(&tag).value(), 0x1000);
+    }
+
+    #[test]
+    fn test_notify_refer_keeps_the_handle() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let handle = Handle::_from_parts(Rc::new(Cell::new(true)), std::ptr::null_mut());
+        let tag_item = TagItem::NuNotifyRefer(handle.clone());
+        assert_eq!(Rc::strong_count(&handle.inner), 2);
+        /* not bound to the stack */
+        assert_eq!(tag_item.value(), 0);
+    }
+
+    #[test]
+    fn test_tag_is_plain_data() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Tag>();
+    }
+
+    #[test]
+    fn test_convert_substate_tag_to_tag_item_and_back() {
+        let tag = Tag::NuSubstate(SubscriptionState::Active);
+        let tag_item: TagItem = (&tag).into();
+        let tag_item_sys = tag_item.item();
+        let new_tag: Tag = TagItem::_from_sys(&tag_item_sys).into();
+
+        assert_eq!(new_tag, tag);
+    }
+
+    #[test]
+    fn test_convert_boolean_tag_to_tag_item_and_back() {
+        let tag = Tag::NuEarlyMedia(true);
+        let tag_item: TagItem = (&tag).into();
+        assert_eq!(tag_item.value(), 1);
+        let new_tag: Tag = TagItem::_from_sys(&tag_item.item()).into();
+        assert_eq!(new_tag, tag);
+
+        let tag_item: TagItem = Tag::NuAutoprack(false).into();
+        assert_eq!(tag_item.value(), 0);
+    }
+
+    #[test]
+    fn test_convert_session_refresher_tag_to_tag_item_and_back() {
+        let tag = Tag::NuSessionRefresher(SessionRefresher::Remote);
+        let tag_item: TagItem = (&tag).into();
+        let new_tag: Tag = TagItem::_from_sys(&tag_item.item()).into();
+        assert_eq!(new_tag, tag);
+
+        let tag = Tag::NuSessionTimer(1800);
+        let tag_item: TagItem = (&tag).into();
+        assert_eq!(tag_item.value(), 1800);
+        let new_tag: Tag = TagItem::_from_sys(&tag_item.item()).into();
+        assert_eq!(new_tag, tag);
+    }
+
+    #[test]
+    fn test_convert_callstate_tag_to_tag_item_and_back() {
+        let tag = Tag::NuCallstate(CallState::Early);
+        let tag_item: TagItem = (&tag).into();
+        let new_tag: Tag = TagItem::_from_sys(&tag_item.item()).into();
+        assert_eq!(new_tag, tag);
+
+        let tag = Tag::NuOfferRecv(true);
+        let tag_item: TagItem = (&tag).into();
+        let new_tag: Tag = TagItem::_from_sys(&tag_item.item()).into();
+        assert_eq!(new_tag, tag);
+    }
+
     #[test]
     fn test_new_tag_type_from_sys() {
         let tt = TagType::_from_sys(unsafe { sys::siptag_content_type_str.as_ptr() });
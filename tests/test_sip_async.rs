@@ -0,0 +1,195 @@
+#![cfg(feature = "async")]
+
+use sofia_sip::aio::EventStream;
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use futures::StreamExt;
+use serial_test::serial;
+
+use std::time::Duration;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        tokio::time::timeout(Duration::from_secs(10), f)
+            .await
+            .expect("timeout")
+    })
+}
+
+/* answer the first request reported by `event` with 200 OK */
+async fn answer(stream: &mut EventStream, event: NuaEvent, sdp: Option<&str>) -> String {
+    while let Some(incoming) = stream.next().await {
+        println!("[NUA _B]Event: {:?}", &incoming.event);
+        if incoming.event == event {
+            let tags = match sdp {
+                Some(sdp) => TagBuilder::default().soatag_user_sdp_str(sdp).collect(),
+                None => vec![],
+            };
+            incoming
+                .handle
+                .as_ref()
+                .unwrap()
+                .respond(200, "OK", &tags)
+                .unwrap();
+            return incoming.sip.payload().as_utf8_lossy();
+        }
+    }
+    panic!("stream ended before {:?}", event);
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_message_async() {
+    /*
+    A                    B
+    |-------MESSAGE----->|
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |handle::message_async|                     |                     |
+    |------------------->[_]      [MESSAGE]     |                     |
+    |                    [_]------------------>[_]  IncomingMessage   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  (stream.next())   |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |        (.await)    [_]<------------------[_]  handle::respond() |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let mut stream_b = nua_b.event_stream();
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("Hi")
+        .collect();
+
+    let (reply, payload) = block_on(async {
+        tokio::join!(
+            handle.message_async(&tags),
+            answer(&mut stream_b, NuaEvent::IncomingMessage, None)
+        )
+    });
+
+    let reply = reply.unwrap();
+    assert_eq!(reply.event, NuaEvent::ReplyMessage);
+    assert_eq!(reply.status, 200);
+    assert_eq!(payload, "Hi");
+
+    block_on(async {
+        nua_a.shutdown_async().await;
+        nua_b.shutdown_async().await;
+    });
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_call_async() {
+    /*
+    A                    B
+    |-------INVITE------>| (handle::invite_async)
+    |<------200 OK-------|
+    |--------ACK-------->|
+    |                    |
+    |---------BYE------->| (handle::bye_async)
+    |<-------200 OK------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let mut stream_b = nua_b.event_stream();
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    let (invite, bye) = block_on(async {
+        let (invite, _) = tokio::join!(
+            handle.invite_async(&tags),
+            answer(
+                &mut stream_b,
+                NuaEvent::IncomingInvite,
+                Some("m=audio 5010 RTP/AVP 8")
+            )
+        );
+        /* the BYE is answered by the stack of B */
+        let bye = handle.bye_async(&[]).await;
+        (invite.unwrap(), bye.unwrap())
+    });
+
+    assert_eq!(invite.event, NuaEvent::ReplyInvite);
+    assert_eq!(invite.status, 200);
+    assert_eq!(bye.event, NuaEvent::ReplyBye);
+    assert_eq!(bye.status, 200);
+}
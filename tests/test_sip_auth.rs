@@ -0,0 +1,468 @@
+use sofia_sip::nua::Credentials;
+use sofia_sip::sip::SipAuth;
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+fn md5_hex(data: &str) -> String {
+    format!("{:x}", md5::compute(data))
+}
+
+/* check the digest response (RFC 2617) of `authorization` as a server */
+fn digest_is_valid(authorization: &SipAuth, method: &str, password: &str) -> bool {
+    let param = |name: &str| authorization.param(name).unwrap_or_default();
+    let ha1 = md5_hex(&format!(
+        "{}:{}:{}",
+        param("username"),
+        param("realm"),
+        password
+    ));
+    let ha2 = md5_hex(&format!("{}:{}", method, param("uri")));
+    let expected = match authorization.param("qop") {
+        Some(qop) => md5_hex(&format!(
+            "{}:{}:{}:{}:{}:{}",
+            ha1,
+            param("nonce"),
+            param("nc"),
+            param("cnonce"),
+            qop,
+            ha2
+        )),
+        None => md5_hex(&format!("{}:{}:{}", ha1, param("nonce"), ha2)),
+    };
+    param("response") == expected
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_register_with_credential_provider() {
+    /*
+    A                    B (registrar)
+    |------REGISTER----->|
+    |<--------401--------| (WWW-Authenticate)
+    |------REGISTER----->| (Authorization, sent by the credential provider)
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |  handle::register() |                     |                     |
+    |------------------->[_]     [REGISTER]     |                     |
+    |                    [_]------------------>[_]  IncomingRegister  |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [401 Unauth] [_]<-------------------|
+    |    ReplyRegister   [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                    [_]     [REGISTER]     |                     |
+    |                    [_]------------------>[_]  IncomingRegister  |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |    ReplyRegister   [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        /* act as a registrar */
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("REGISTER")
+            .nutag_registrar(nua_b_url)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* (username, realm) of the Authorization received by B */
+    let b_authorization = Rc::new(RefCell::new(None::<(String, String)>));
+    /* every final response received by A */
+    let a_replies = Rc::new(RefCell::new(Vec::<u32>::new()));
+
+    {
+        let b_authorization = b_authorization.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingRegister = event {
+                    let handle = handle.unwrap();
+                    match sip.authorization().first() {
+                        None => {
+                            let tags = TagBuilder::default()
+                                .siptag_www_authenticate_str(
+                                    "Digest realm=\"sofia-sip\", nonce=\"4a6f1f3b\", algorithm=MD5",
+                                )
+                                .collect();
+                            handle.respond(401, "Unauthorized", &tags).unwrap();
+                        }
+                        Some(authorization) => {
+                            assert!(digest_is_valid(authorization, "REGISTER", "secret"));
+                            b_authorization.replace(Some((
+                                authorization.username().unwrap_or_default(),
+                                authorization.realm().unwrap_or_default(),
+                            )));
+                            let tags = TagBuilder::default()
+                                .siptag_contact_str("<sip:alice@127.0.0.1:5080>;expires=60")
+                                .siptag_expires_str("60")
+                                .collect();
+                            handle.respond(200, "OK", &tags).unwrap();
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    nua_a.credential_provider(|realm: &str| match realm {
+        "sofia-sip" => Some(Credentials::new("alice", "secret")),
+        _ => None,
+    });
+
+    {
+        let a_replies = a_replies.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::ReplyRegister = event {
+                    if status >= 200 {
+                        a_replies.borrow_mut().push(status);
+                    }
+                    if status == 200 {
+                        nua.root().break_();
+                    }
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str("sip:alice@127.0.0.1")
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .nutag_registrar(nua_b_url)
+        .nutag_m_username("alice")
+        .nutag_outbound("no-options-keepalive, no-validate")
+        .collect();
+
+    handle.register(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    /* the application still sees the challenge */
+    assert_eq!(*a_replies.borrow(), vec![401, 200]);
+    assert_eq!(
+        *b_authorization.borrow(),
+        Some(("alice".to_string(), "sofia-sip".to_string()))
+    );
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_register_with_wrong_credentials() {
+    /*
+    A                    B (registrar)
+    |------REGISTER----->|
+    |<--------401--------| (WWW-Authenticate)
+    |------REGISTER----->| (Authorization, wrong password)
+    |<--------401--------|
+    |------REGISTER----->| (Authorization, wrong password)
+    |<--------401--------| (given up, the application sees the failure)
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        /* act as a registrar */
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("REGISTER")
+            .nutag_registrar(nua_b_url)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* digests rejected by B */
+    let b_rejected = Rc::new(RefCell::new(0));
+    /* every final response received by A */
+    let a_replies = Rc::new(RefCell::new(Vec::<u32>::new()));
+
+    {
+        let b_rejected = b_rejected.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingRegister = event {
+                    let handle = handle.unwrap();
+                    if let Some(authorization) = sip.authorization().first() {
+                        if digest_is_valid(authorization, "REGISTER", "secret") {
+                            handle.respond(200, "OK", &[]).unwrap();
+                            return;
+                        }
+                        *b_rejected.borrow_mut() += 1;
+                    }
+                    let tags = TagBuilder::default()
+                        .siptag_www_authenticate_str(
+                            "Digest realm=\"sofia-sip\", nonce=\"4a6f1f3b\", algorithm=MD5",
+                        )
+                        .collect();
+                    handle.respond(401, "Unauthorized", &tags).unwrap();
+                }
+            },
+        );
+    }
+
+    nua_a.credential_provider(Credentials::new("alice", "wrong"));
+
+    {
+        let a_replies = a_replies.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::ReplyRegister = event {
+                    if status < 200 {
+                        return;
+                    }
+                    a_replies.borrow_mut().push(status);
+                    /* the challenge and the two rejected answers */
+                    if status != 401 || a_replies.borrow().len() == 3 {
+                        nua.root().break_();
+                    }
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str("sip:alice@127.0.0.1")
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .nutag_registrar(nua_b_url)
+        .nutag_m_username("alice")
+        .nutag_outbound("no-options-keepalive, no-validate")
+        .collect();
+
+    handle.register(&tags);
+
+    let root = Root::get_default_root().unwrap();
+    root.run();
+    /* the last 401 is not answered */
+    root.sleep(500);
+
+    assert_eq!(*a_replies.borrow(), vec![401, 401, 401]);
+    assert_eq!(*b_rejected.borrow(), 2);
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_message_with_manual_authenticate() {
+    /*
+    A                    B (proxy)
+    |-------MESSAGE----->|
+    |<--------407--------| (Proxy-Authenticate)
+    |-------MESSAGE----->| (Proxy-Authorization, handle::authenticate_with())
+    |<--------200--------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* (username, realm) of the Proxy-Authorization received by B */
+    let b_authorization = Rc::new(RefCell::new(None::<(String, String)>));
+    /* every final response received by A */
+    let a_replies = Rc::new(RefCell::new(Vec::<u32>::new()));
+
+    {
+        let b_authorization = b_authorization.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingMessage = event {
+                    let handle = handle.unwrap();
+                    match sip.proxy_authorization().first() {
+                        None => {
+                            let tags = TagBuilder::default()
+                                .siptag_proxy_authenticate_str(
+                                    "Digest realm=\"trunk\", nonce=\"1c2b3a\", algorithm=MD5",
+                                )
+                                .collect();
+                            handle
+                                .respond(407, "Proxy Authentication Required", &tags)
+                                .unwrap();
+                        }
+                        Some(authorization) => {
+                            assert!(digest_is_valid(authorization, "MESSAGE", "trunk-secret"));
+                            b_authorization.replace(Some((
+                                authorization.username().unwrap_or_default(),
+                                authorization.realm().unwrap_or_default(),
+                            )));
+                            handle.respond(200, "OK", &[]).unwrap();
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    {
+        let a_replies = a_replies.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyMessage if status == 407 => {
+                        a_replies.borrow_mut().push(status);
+                        let credentials = Credentials::new("bob", "trunk-secret");
+                        assert!(handle.unwrap().authenticate_with(&sip, &credentials));
+                    }
+                    NuaEvent::ReplyMessage if status >= 200 => {
+                        a_replies.borrow_mut().push(status);
+                        nua.root().break_();
+                    }
+                    NuaEvent::ReplyAuthenticate => {
+                        panic!("authenticate failed: {} {}", status, phrase);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("Hi")
+        .collect();
+
+    handle.message(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(*a_replies.borrow(), vec![407, 200]);
+    assert_eq!(
+        *b_authorization.borrow(),
+        Some(("bob".to_string(), "trunk".to_string()))
+    );
+}
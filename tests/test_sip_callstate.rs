@@ -0,0 +1,173 @@
+use sofia_sip::nua::CallStateInfo;
+use sofia_sip::su;
+use sofia_sip::tag::CallState;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_call_states() {
+    /*
+    A                    B
+    |-------INVITE------>| (A: calling, offer sent / B: received, offer received)
+    |<------200 OK-------| (B: completed, answer sent)
+    |--------ACK-------->| (A: ready, answer received / B: ready)
+    |                    |
+    |---------BYE------->| (A: terminating)
+    |<-------200 OK------| (A and B: terminated)
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |   handle::invite()  |                     |                     |
+    |------------------->[_]      [INVITE]      |                     |
+    |    IncomingState   [_]------------------>[_]   IncomingInvite   |
+    |<------------------ [_]                   [_]------------------->|
+    |                    [_]                   [_]    IncomingState   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |                    [_]<------------------[_]    IncomingState   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]        [ACK]      [_]                    |
+    |    IncomingState   [_]------------------>[_]    IncomingState   |
+    |<------------------ [_]                   [_]------------------->|
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_states = Rc::new(RefCell::new(Vec::<CallStateInfo>::new()));
+    let b_states = Rc::new(RefCell::new(Vec::<CallStateInfo>::new()));
+
+    {
+        let b_states = b_states.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        handle.unwrap().respond(200, "OK", &tags).unwrap();
+                    }
+                    NuaEvent::IncomingState => {
+                        b_states
+                            .borrow_mut()
+                            .push(CallStateInfo::from_tags(&tags).unwrap());
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    {
+        let a_states = a_states.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingState = event {
+                    let info = CallStateInfo::from_tags(&tags).unwrap();
+                    a_states.borrow_mut().push(info);
+                    match info.state {
+                        CallState::Ready => handle.unwrap().bye(&[]),
+                        CallState::Terminated => nua.root().break_(),
+                        _ => {}
+                    }
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str(nua_b_url)
+            .nutag_url(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    let a_states = a_states.borrow();
+    assert_eq!(a_states.first().unwrap().state, CallState::Calling);
+    assert!(a_states.first().unwrap().offer_sent);
+    let a_ready = a_states.iter().find(|s| s.state == CallState::Ready);
+    assert!(a_ready.unwrap().answer_recv);
+    assert_eq!(a_states.last().unwrap().state, CallState::Terminated);
+
+    let b_states = b_states.borrow();
+    assert_eq!(b_states.first().unwrap().state, CallState::Received);
+    assert!(b_states.first().unwrap().offer_recv);
+    assert!(b_states.iter().any(|s| s.answer_sent));
+    assert!(b_states.iter().any(|s| s.state == CallState::Ready));
+}
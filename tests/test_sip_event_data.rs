@@ -0,0 +1,140 @@
+use sofia_sip::nua::EventData;
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::Root;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_register_with_event_data() {
+    /*
+    A                    B (registrar)
+    |------REGISTER----->|
+    |<--------200--------| (Contact and Expires)
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |  handle::register() |                     |                     |
+    |------------------->[_]     [REGISTER]     |                     |
+    |                    [_]------------------>[_]  IncomingRegister  |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |    ReplyRegister   [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        /* act as a registrar */
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("REGISTER")
+            .nutag_registrar(nua_b_url)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* method of the request received by B */
+    let b_method = Rc::new(RefCell::new(None::<String>));
+    /* (status, expires, contacts) of the final response received by A */
+    let a_reply = Rc::new(RefCell::new(None::<(u32, Option<u32>, Vec<String>)>));
+
+    {
+        let b_method = b_method.clone();
+        nua_b.event_callback(move |_nua: &mut Nua, data: EventData| {
+            println!("[NUA _B]Event: {:?}", &data);
+            if let EventData::IncomingRegister { handle, request } = data {
+                b_method.replace(request.method().cloned());
+                let tags = TagBuilder::default()
+                    .siptag_contact_str("<sip:alice@127.0.0.1:5080>;expires=60")
+                    .siptag_expires_str("3600")
+                    .collect();
+                handle.respond(200, "OK", &tags).unwrap();
+            }
+        });
+    }
+
+    {
+        let a_reply = a_reply.clone();
+        nua_a.event_callback(move |nua: &mut Nua, data: EventData| {
+            println!("[NUA A_]Event: {:?}", &data);
+            match data {
+                EventData::ReplyRegister {
+                    status,
+                    expires,
+                    contacts,
+                    ..
+                } if status >= 200 => {
+                    let contacts = contacts.iter().map(|c| c.url().clone()).collect();
+                    a_reply.replace(Some((status, expires, contacts)));
+                    nua.root().break_();
+                }
+                EventData::Raw(raw) => {
+                    println!("[NUA A_]Raw event: {:?}", &raw.event);
+                }
+                _ => {}
+            }
+        });
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str("sip:alice@127.0.0.1")
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .nutag_registrar(nua_b_url)
+        .nutag_m_username("alice")
+        .nutag_outbound("no-options-keepalive, no-validate")
+        .collect();
+
+    handle.register(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(*b_method.borrow(), Some("REGISTER".to_string()));
+    assert_eq!(
+        *a_reply.borrow(),
+        Some((200, Some(60), vec!["sip:alice@127.0.0.1:5080".to_string()]))
+    );
+}
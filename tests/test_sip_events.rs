@@ -0,0 +1,115 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::time::Duration;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_message_with_events_receiver() {
+    /*
+    A                    B
+    |-------MESSAGE----->|
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |  handle::message()  |                     |                     |
+    |------------------->[_]      [MESSAGE]     |                     |
+    |                    [_]------------------>[_]  IncomingMessage   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  (events_b.recv()) |
+    |                    [_]      [200 OK]     [_]                    |
+    |    ReplyMessage    [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    | (events_a.recv())   |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let events_a = nua_a.events();
+    let events_b = nua_b.events();
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("Hi")
+        .collect();
+    handle.message(&tags);
+
+    /* both stacks use the default root, running it on behalf of B also
+    delivers the events of A */
+    let incoming = events_b.recv_timeout(Duration::from_secs(5)).unwrap();
+    println!("[NUA _B]Event: {:?}", &incoming.event);
+    assert!(matches!(incoming.event, NuaEvent::IncomingMessage));
+    assert_eq!(incoming.sip.payload().as_utf8_lossy(), "Hi");
+    incoming
+        .handle
+        .as_ref()
+        .unwrap()
+        .respond(200, "OK", &[])
+        .unwrap();
+
+    let mut a_events = Vec::new();
+    while let Some(event) = events_a.recv_timeout(Duration::from_secs(5)) {
+        println!(
+            "[NUA A_]Event: {:?} // status: {:?}",
+            &event.event, &event.status
+        );
+        let final_reply = event.status >= 200;
+        a_events.push((event.event, event.status));
+        if final_reply {
+            break;
+        }
+    }
+
+    assert_eq!(a_events.len(), 1);
+    assert!(matches!(a_events[0], (NuaEvent::ReplyMessage, 200)));
+    /* nothing else happened on B */
+    assert!(events_b.try_recv().is_none());
+}
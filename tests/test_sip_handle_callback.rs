@@ -0,0 +1,220 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+/* dialog state of the application, reached from the handle */
+#[derive(Debug, Default)]
+struct Call {
+    events: RefCell<Vec<NuaEvent>>,
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_handle_callback_and_user_data() {
+    /*
+    A                    B
+    |-------INVITE------>|
+    |<------200 OK-------|
+    |--------ACK-------->|
+    |                    |
+    |---------BYE------->|
+    |<-------200 OK------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |   handle::invite()  |                     |                     |
+    |------------------->[_]      [INVITE]      |                     |
+    |                    [_]------------------>[_]   IncomingInvite   |
+    |                    [_]                   [_]------------------->| (nua callback)
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |                    [_]<------------------[_]                    |
+    |                    [_]        [ACK]      [_]                    |
+    |   IncomingActive   [_]------------------>[_]   IncomingActive   |
+    |<------------------ [_]                   [_]------------------->| (handle callback)
+    |    handle::bye()   [_]                   [_]                    |
+    |------------------->[_]        [BYE]      [_]                    |
+    |                    [_]------------------>[_]    IncomingBye     |
+    |                    [_]                   [_]------------------->| (handle callback)
+    |                    [_]      [200 OK]     [_]                    |
+    |      ReplyBye      [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* events with a handle received by the callbacks of the stacks */
+    let a_nua_events = Rc::new(Cell::new(0));
+    let b_nua_events = Rc::new(Cell::new(0));
+    let b_call: Rc<RefCell<Option<Handle>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_nua_events = b_nua_events.clone();
+        let b_call = b_call.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if handle.is_some() {
+                    b_nua_events.set(b_nua_events.get() + 1);
+                }
+                if let NuaEvent::IncomingInvite = event {
+                    let handle = handle.unwrap();
+                    handle.set_user_data(Call::default());
+                    handle.callback(
+                        |_nua: &mut Nua,
+                         event: NuaEvent,
+                         status: u32,
+                         phrase: String,
+                         handle: &Handle,
+                         _sip: Sip,
+                         _tags: Vec<Tag>| {
+                            println!(
+                                "[CALL _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                                &event, &status, &phrase
+                            );
+                            let call = handle.user_data::<Call>().unwrap();
+                            call.events.borrow_mut().push(event);
+                        },
+                    );
+                    let tags = TagBuilder::default()
+                        .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                        .collect();
+                    handle.respond(200, "OK", &tags).unwrap();
+                    /* keep the call alive */
+                    b_call.replace(Some(handle.clone()));
+                }
+            },
+        );
+    }
+
+    {
+        let a_nua_events = a_nua_events.clone();
+        nua_a.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if handle.is_some() {
+                    a_nua_events.set(a_nua_events.get() + 1);
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str(nua_b_url)
+            .nutag_url(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+    handle.set_user_data(Call::default());
+    handle.callback(
+        |nua: &mut Nua,
+         event: NuaEvent,
+         status: u32,
+         phrase: String,
+         handle: &Handle,
+         _sip: Sip,
+         _tags: Vec<Tag>| {
+            println!(
+                "[CALL A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                &event, &status, &phrase
+            );
+            let call = handle.user_data::<Call>().unwrap();
+            call.events.borrow_mut().push(event.clone());
+            match event {
+                NuaEvent::IncomingActive => handle.bye(&[]),
+                NuaEvent::ReplyBye if status >= 200 => nua.root().break_(),
+                _ => {}
+            }
+        },
+    );
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    /* every event of the handles went to their own callback */
+    assert_eq!(a_nua_events.get(), 0);
+    assert_eq!(b_nua_events.get(), 1); /* IncomingInvite */
+
+    let a_call = handle.user_data::<Call>().unwrap();
+    assert!(a_call.events.borrow().contains(&NuaEvent::ReplyInvite));
+    assert!(a_call.events.borrow().contains(&NuaEvent::IncomingActive));
+    assert_eq!(a_call.events.borrow().last(), Some(&NuaEvent::ReplyBye));
+
+    let b_call = b_call.borrow().clone().unwrap();
+    let b_call = b_call.user_data::<Call>().unwrap();
+    assert!(b_call.events.borrow().contains(&NuaEvent::IncomingActive));
+    assert!(b_call.events.borrow().contains(&NuaEvent::IncomingBye));
+
+    /* the data is not of this type */
+    assert!(handle.user_data::<String>().is_none());
+}
@@ -0,0 +1,304 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::SipRef;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_message_headers() {
+    /*
+    A                    B
+    |-------MESSAGE----->|
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |  handle::message()  |                     |                     |
+    |------------------->[_]     [MESSAGE]      |                     |
+    |                    [_]------------------>[_]  IncomingMessage   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]      [200 OK]     [_]                    |
+    |    ReplyMessage    [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let b_request: Rc<RefCell<Option<Sip>>> = Rc::new(RefCell::new(None));
+    let a_response: Rc<RefCell<Option<Sip>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_request = b_request.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingMessage = event {
+                    /* the stack answers 200 OK by itself */
+                    b_request.replace(Some(sip));
+                }
+            },
+        );
+    }
+
+    {
+        let a_response = a_response.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::ReplyMessage = event {
+                    if status >= 200 {
+                        a_response.replace(Some(sip));
+                        nua.root().break_();
+                    }
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_supported_str("timer, 100rel")
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("hi")
+        .collect();
+    handle.message(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    let request = b_request.borrow_mut().take().unwrap();
+    let request_line = request.request().unwrap();
+    assert_eq!(request_line.method(), "MESSAGE");
+    assert_eq!(request_line.version(), "SIP/2.0");
+    assert!(request.status().is_none());
+    assert!(!request.call_id().id().is_empty());
+    assert_eq!(request.cseq().method(), "MESSAGE");
+    assert_eq!(request.via().len(), 1);
+    assert_eq!(request.via()[0].transport(), "UDP");
+    assert!(request.via()[0].branch().unwrap().starts_with("z9hG4bK"));
+    assert_eq!(request.max_forwards().count(), Some(70));
+    assert!(request.supported().contains("100rel"));
+    assert!(request.route().is_empty());
+
+    let response = a_response.borrow_mut().take().unwrap();
+    let status_line = response.status().unwrap();
+    assert_eq!(status_line.status(), 200);
+    assert_eq!(status_line.phrase(), "OK");
+    assert!(response.request().is_none());
+    assert_eq!(response.call_id().id(), request.call_id().id());
+    assert_eq!(response.cseq().seq(), request.cseq().seq());
+    assert_eq!(response.via()[0].branch(), request.via()[0].branch());
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_unknown_headers() {
+    /*
+    A                    B
+    |-------MESSAGE----->| (X-Account-Id, X-Carrier)
+    |<--------200--------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let b_request: Rc<RefCell<Option<Sip>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_request = b_request.clone();
+        nua_b.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingMessage = event {
+                    b_request.replace(Some(sip));
+                    nua.root().break_();
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_header_str("X-Account-Id: 42\r\nX-Carrier: example")
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("hi")
+        .collect();
+    handle.message(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    let request = b_request.borrow_mut().take().unwrap();
+    assert_eq!(request.unknown_header("x-account-id"), Some("42"));
+    assert_eq!(request.unknown_header("X-Carrier"), Some("example"));
+    let names: Vec<&str> = request.unknown_headers().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["X-Account-Id", "X-Carrier"]);
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_callback_ref() {
+    /*
+    A                    B
+    |-------MESSAGE----->| (B borrows the request with `callback_ref`)
+    |<--------200--------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let b_payload: Rc<RefCell<Option<Vec<u8>>>> = Rc::new(RefCell::new(None));
+    let b_request: Rc<RefCell<Option<Sip>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_payload = b_payload.clone();
+        let b_request = b_request.clone();
+        nua_b.callback_ref(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  sip: SipRef,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingMessage = event {
+                    assert_eq!(sip.method().unwrap(), "MESSAGE");
+                    assert_eq!(sip.content_type().unwrap(), "text/plain");
+                    assert_eq!(sip.unknown_header("x-account-id").unwrap(), "42");
+                    b_payload.replace(Some(sip.payload().to_vec()));
+                    b_request.replace(Some(sip.to_sip()));
+                    nua.root().break_();
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_header_str("X-Account-Id: 42")
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("hi")
+        .collect();
+    handle.message(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(b_payload.borrow_mut().take().unwrap(), b"hi");
+    let request = b_request.borrow_mut().take().unwrap();
+    assert_eq!(request.cseq().method(), "MESSAGE");
+    assert_eq!(request.payload().as_utf8_lossy(), "hi");
+    assert_eq!(request.unknown_header("X-Account-Id"), Some("42"));
+}
@@ -0,0 +1,156 @@
+use sofia_sip::su;
+use sofia_sip::Dtmf;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_info_dtmf_relay() {
+    /*
+    A                    B
+    |-------INVITE------>|
+    |<--------200--------|
+    |---------ACK------->|
+    |                    |
+    |--------INFO------->| (Signal=1)
+    |<--------200--------|
+    |--------INFO------->| (Signal=#)
+    |<--------200--------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let digits = vec![Dtmf::new('1', 160).unwrap(), Dtmf::new('#', 100).unwrap()];
+    let b_digits = Rc::new(RefCell::new(Vec::<Dtmf>::new()));
+    let a_info_replies = Rc::new(RefCell::new(Vec::<u32>::new()));
+
+    {
+        let b_digits = b_digits.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let handle = handle.unwrap();
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        /* the incoming call is kept by the stack after the callback returns */
+                        handle.respond(200, "OK", &tags).unwrap();
+                    }
+                    NuaEvent::IncomingInfo => {
+                        b_digits.borrow_mut().push(sip.dtmf().unwrap());
+                        if status < 200 {
+                            handle.unwrap().respond(200, "OK", &[]).unwrap();
+                        }
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    {
+        let a_info_replies = a_info_replies.clone();
+        let digits = digits.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingActive => {
+                        handle.unwrap().info_dtmf(&digits[0], &[]);
+                    }
+                    NuaEvent::ReplyInfo if status >= 200 => {
+                        a_info_replies.borrow_mut().push(status);
+                        let sent = a_info_replies.borrow().len();
+                        if sent < digits.len() {
+                            handle.unwrap().info_dtmf(&digits[sent], &[]);
+                        } else {
+                            nua.root().break_();
+                        }
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(*a_info_replies.borrow(), vec![200, 200]);
+    assert_eq!(*b_digits.borrow(), digits);
+}
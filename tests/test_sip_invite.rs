@@ -10,6 +10,11 @@ use sofia_sip::TagBuilder;
 use adorn::adorn;
 use serial_test::serial;
 
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
 fn wrap(f: fn()) {
     /* manual deinit (because tests do not run atexit) */
     if let Err(e) = std::panic::catch_unwind(|| {
@@ -31,6 +36,196 @@ fn wrap(f: fn()) {
     }
 }
 
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_basic_call() {
+    // see <lib-sofia-ua-c>/tests/test_basic_call.c::test_basic_call_1
+    // A                    B
+    // |-------INVITE------>|
+    // |<----100 Trying-----|
+    // |                    |
+    // |<----180 Ringing----|
+    // |                    |
+    // |<------200 OK-------|
+    // |--------ACK-------->|
+    // |                    |
+    // |<-------BYE---------|
+    // |-------200 OK------>|
+    // |                    |
+
+    //                        ______(NETWORK)_____
+    //                       /                    \
+    // A                 NUA STACK (A)         NUA STACK (B)             B
+    // |                     |                     |                     |
+    // |   nua::handle(B)    |                     |                     |
+    // |-------------------->|                     |                     |
+    // |                     |                     |                     |
+    // |  handle::invite()   |                     |                     |
+    // |------------------->[_]    [INVITE/SDP]    |                     |
+    // |                    [_]------------------>[_]   IncomingInvite   |
+    // |                    [_]                   [_]------------------->|
+    // |                    [_]                   [_]   nua::handle(A)   |
+    // |                    [_]                   [_]                    |
+    // |                    [_]    [100 Trying]   [_]                    |
+    // |                    [_]<------------------[_]                    |
+    // |                    [_]   [180 Ringing]   [_]                    |
+    // |                    [_]<------------------[_]                    |
+    // |                    [_]                   [_]  handle::respond() |
+    // |                    [_]      [200 OK]     [_]<-------------------|
+    // |                    [_]<------------------[_]                    |
+    // |     ReplyInvite    [_]                   [_]                    |
+    // |<-------------------[_]       [ACK]       [_]                    |
+    // |                    [_]------------------>[_]   IncomingActive   |
+    // |   IncomingActive   [_]                   [_]------------------->|
+    // |<-------------------[_]                   [_]                    |
+    // |                    [_]                   [_]    handle::bye()   |
+    // |                    [_]       [BYE]       [_]<-------------------|
+    // |    IncomingBye     [_]<------------------[_]                    |
+    // |<-------------------[_]      [200 OK]     [_]                    |
+    // |                    [_]------------------>[_]                    |
+    // |                     |                     |                     |
+    // |                     |                     |                     |
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let url = Tag::NuUrl(nua_a_url.into());
+        let tags = TagBuilder::default().tag(url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let url = Tag::NuUrl(nua_b_url.into());
+        let tags = TagBuilder::default().tag(url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_active = Rc::new(Cell::new(false));
+    let b_active = Rc::new(Cell::new(false));
+    let a_final_status = Rc::new(Cell::new(0));
+    let a_received_bye = Rc::new(Cell::new(false));
+    let b_bye_status = Rc::new(Cell::new(0));
+    /* B answers and hangs up the incoming call from other events */
+    let b_call: Rc<RefCell<Option<Handle>>> = Rc::new(RefCell::new(None));
+
+    {
+        /* NUA B */
+        let a_active = a_active.clone();
+        let b_active = b_active.clone();
+        let b_call = b_call.clone();
+        let b_bye_status = b_bye_status.clone();
+        nua_b.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  tags: Vec<Tag>| {
+                // dbg!(&nua, &event, &status, &phrase, &handle, &sip, &tags);
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let handle = handle.unwrap();
+                        handle.respond(180, "Ringing", &[]).unwrap();
+                        *b_call.borrow_mut() = Some(handle.clone());
+                    }
+                    NuaEvent::IncomingActive => {
+                        b_active.set(true);
+                        if a_active.get() {
+                            b_call.borrow().as_ref().unwrap().bye(&[]);
+                        }
+                    }
+                    NuaEvent::ReplyBye if status >= 200 => {
+                        b_bye_status.set(status);
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    {
+        /* NUA A */
+        let a_active = a_active.clone();
+        let b_active = b_active.clone();
+        let a_final_status = a_final_status.clone();
+        let b_call = b_call.clone();
+        let a_received_bye = a_received_bye.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  tags: Vec<Tag>| {
+                // dbg!(&nua, &event, &status, &phrase, &handle, &sip, &tags);
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyInvite if status == 180 => {
+                        /* A is alerted, now B answers the call */
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        let b_call = b_call.borrow();
+                        b_call.as_ref().unwrap().respond(200, "OK", &tags).unwrap();
+                    }
+                    NuaEvent::ReplyInvite if status >= 200 => {
+                        a_final_status.set(status);
+                    }
+                    NuaEvent::IncomingActive => {
+                        a_active.set(true);
+                        if b_active.get() {
+                            b_call.borrow().as_ref().unwrap().bye(&[]);
+                        }
+                    }
+                    NuaEvent::IncomingBye => {
+                        a_received_bye.set(true);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .tag(Tag::SipToStr(nua_b_url.into()))
+            .tag(Tag::NuUrl(nua_b_url.into()))
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+    dbg!(&handle);
+
+    let tags = TagBuilder::default()
+        .tag(Tag::NuUrl(nua_b_url.into()))
+        .tag(Tag::SoaUserSdpStr("m=audio 5008 RTP/AVP 8".into()))
+        .tag(Tag::NuMUsername("a+a".into()))
+        .tag(Tag::NuMDisplay("Alice".into()))
+        .collect();
+
+    handle.invite(&tags);
+
+    println!("--> Root run start");
+    Root::get_default_root().unwrap().run();
+    println!("--> Root run end");
+
+    assert_eq!(a_final_status.get(), 200);
+    assert!(a_active.get());
+    assert!(b_active.get());
+    assert!(a_received_bye.get());
+    assert_eq!(b_bye_status.get(), 200);
+
+    println!("--> Test end");
+}
+
 #[test]
 #[adorn(wrap)]
 #[serial]
@@ -162,3 +357,301 @@ fn test_case_basic_call_incomplete() {
     println!("--> Test end");
     // assert!(false);
 }
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_call_rejected_with_busy() {
+    // A                    B
+    // |-------INVITE------>|
+    // |<----100 Trying-----|
+    // |                    |
+    // |<-----486 Busy------|
+    // |--------ACK-------->|
+    // |                    |
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let url = Tag::NuUrl(nua_a_url.into());
+        let tags = TagBuilder::default().tag(url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let url = Tag::NuUrl(nua_b_url.into());
+        let tags = TagBuilder::default().tag(url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_final_status = Rc::new(Cell::new(0));
+
+    nua_b.callback(
+        |_nua: &mut Nua,
+         event: NuaEvent,
+         status: u32,
+         phrase: String,
+         handle: Option<&Handle>,
+         _sip: Sip,
+         _tags: Vec<Tag>| {
+            println!(
+                "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                &event, &status, &phrase
+            );
+            if let NuaEvent::IncomingInvite = event {
+                handle.unwrap().respond(486, "Busy Here", &[]).unwrap();
+            }
+        },
+    );
+
+    {
+        let a_final_status = a_final_status.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyInvite if status >= 200 => {
+                        a_final_status.set(status);
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str(nua_b_url)
+            .nutag_url(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .nutag_url(nua_b_url)
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(a_final_status.get(), 486);
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_call_canceled() {
+    // A                    B
+    // |-------INVITE------>|
+    // |<----100 Trying-----|
+    // |                    |
+    // |<----180 Ringing----|
+    // |                    |
+    // |-------CANCEL------>|
+    // |<------200 OK-------|
+    // |<-----487 Cancel----|
+    // |--------ACK-------->|
+    // |                    |
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_cancel_status = Rc::new(Cell::new(0));
+    let a_invite_status = Rc::new(Cell::new(0));
+    let b_received_cancel = Rc::new(Cell::new(false));
+
+    {
+        let b_received_cancel = b_received_cancel.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let handle = handle.unwrap();
+                        handle.respond(180, "Ringing", &[]).unwrap();
+                    }
+                    NuaEvent::IncomingCancel => {
+                        b_received_cancel.set(true);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    {
+        let a_cancel_status = a_cancel_status.clone();
+        let a_invite_status = a_invite_status.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyInvite if status == 180 => {
+                        handle.unwrap().cancel(&[]);
+                    }
+                    NuaEvent::ReplyCancel => {
+                        a_cancel_status.set(status);
+                    }
+                    NuaEvent::ReplyInvite if status >= 200 => {
+                        a_invite_status.set(status);
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str(nua_b_url)
+            .nutag_url(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .nutag_url(nua_b_url)
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(a_cancel_status.get(), 200);
+    assert_eq!(a_invite_status.get(), 487);
+    assert!(b_received_cancel.get());
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_call_destroy_and_wait() {
+    // A                    B
+    // |-------INVITE------>|
+    // |<------200 OK-------|
+    // |--------ACK-------->|
+    // |                    |
+    // |---------BYE------->| (destroy_and_wait)
+    // |<-------200 OK------|
+    // |                    |
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let b_received_bye = Rc::new(Cell::new(false));
+
+    {
+        let b_received_bye = b_received_bye.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let handle = handle.unwrap();
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        handle.respond(200, "OK", &tags).unwrap();
+                    }
+                    NuaEvent::IncomingBye => {
+                        b_received_bye.set(true);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    nua_a.callback(
+        |nua: &mut Nua,
+         event: NuaEvent,
+         status: u32,
+         phrase: String,
+         _handle: Option<&Handle>,
+         _sip: Sip,
+         _tags: Vec<Tag>| {
+            println!(
+                "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                &event, &status, &phrase
+            );
+            if let NuaEvent::IncomingActive = event {
+                nua.root().break_();
+            }
+        },
+    );
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str(nua_b_url)
+            .nutag_url(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .nutag_url(nua_b_url)
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+    assert!(handle.has_active_call());
+
+    handle.destroy_and_wait(Duration::from_secs(5)).unwrap();
+
+    assert!(b_received_bye.get());
+}
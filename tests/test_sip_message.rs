@@ -12,6 +12,7 @@ use serial_test::serial;
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 fn wrap(f: fn()) {
     /* manual deinit (because tests do not run atexit) */
@@ -267,3 +268,42 @@ fn test_case_nua_a_send_message_to_nua_b() {
 
     assert_eq!(&*recv_message.borrow(), &my_message);
 }
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_handle_outlives_nua() {
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str(nua_b_url)
+            .nutag_url(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    /* the C handle is released with the stack */
+    drop(nua_a);
+
+    let tags = TagBuilder::default()
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("Hi Sofia SIP\n")
+        .collect();
+
+    /* ignored */
+    handle.message(&tags);
+    handle.bye(&[]);
+    assert!(handle.destroy_and_wait(Duration::from_secs(1)).is_ok());
+    assert!(!handle.has_active_call());
+    assert!(!handle.has_call_on_hold());
+    assert!(handle.respond(200, "OK", &[]).is_err());
+    assert!(handle.make_replaces(false).is_none());
+
+    drop(handle);
+}
@@ -0,0 +1,139 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_extension_method() {
+    /*
+    A                    B
+    |---------FOO------->|
+    |<--------200--------| (answered by application B)
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |  handle::method()   |                     |                     |
+    |------------------->[_]       [FOO]        |                     |
+    |                    [_]------------------>[_]   IncomingMethod   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |    ReplyMethod     [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("FOO")
+            .nutag_appl_method("FOO")
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_method_status = Rc::new(Cell::new(0));
+    let b_received_foo = Rc::new(Cell::new(false));
+
+    {
+        let b_received_foo = b_received_foo.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingMethod = event {
+                    b_received_foo.set(sip.method().map(|m| m.as_str()) == Some("FOO"));
+                    handle.unwrap().respond(200, "OK", &[]).unwrap();
+                }
+            },
+        );
+    }
+
+    {
+        let a_method_status = a_method_status.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::ReplyMethod = event {
+                    if status >= 200 {
+                        a_method_status.set(status);
+                        nua.root().break_();
+                    }
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    handle.method("FOO", &[]);
+
+    Root::get_default_root().unwrap().run();
+
+    assert!(b_received_foo.get());
+    assert_eq!(a_method_status.get(), 200);
+}
@@ -0,0 +1,210 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use sofia_sip::sip::SubscriptionState;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_notifier_fan_out_to_watchers() {
+    /*
+    A1, A2 (watchers)         B (notifier)
+    |-------SUBSCRIBE-------->| (from A1 and A2)
+    |<---------202------------|
+    |<----NOTIFY (pending)----|
+    |                         | (B authorizes each watcher)
+    |<-NOTIFY (active, open)--|
+    |                         | (B publishes a new state)
+    |<-NOTIFY (active,closed)-| (to A1 and A2)
+    |                         | (B terminates the notifier)
+    |<--NOTIFY (terminated)---|
+    |                         |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |  handle::notifier() |
+    |                     |                     |<--------------------|
+    | handle::subscribe() |                     |                     |
+    |------------------->[_]    [SUBSCRIBE]     |                     |
+    |                    [_]------------------>[_]IncomingSubscription|
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_] handle::authorize()|
+    |                    [_]      [NOTIFY]     [_]<-------------------|
+    |    IncomingNotify  [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("SUBSCRIBE")
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* payloads of each active NOTIFY received by A */
+    let a_notifies = Rc::new(RefCell::new(Vec::<String>::new()));
+    let a_terminated = Rc::new(RefCell::new(0));
+    let b_authorized = Rc::new(RefCell::new(0));
+
+    let notifier = Handle::create(&nua_b, &[]).unwrap();
+
+    {
+        let b_authorized = b_authorized.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingSubscription = event {
+                    let sub = tags
+                        .iter()
+                        .find(|tag| matches!(tag, Tag::NeaSub(_)))
+                        .unwrap()
+                        .clone();
+                    assert!(tags.contains(&Tag::NuSubstate(SubscriptionState::Pending)));
+                    let tags = TagBuilder::default()
+                        .tag(sub)
+                        .nutag_substate(SubscriptionState::Active)
+                        .collect();
+                    /* the watcher was just reported, it is alive */
+                    unsafe { handle.unwrap().authorize(&tags) };
+                    *b_authorized.borrow_mut() += 1;
+                }
+            },
+        );
+    }
+
+    {
+        let tags = TagBuilder::default()
+            .siptag_event_str("presence")
+            .siptag_content_type_str("text/plain")
+            .siptag_payload_str("open")
+            .nutag_substate(SubscriptionState::Pending)
+            .collect();
+        notifier.notifier(&tags);
+    }
+
+    let watchers: Vec<Handle> = (0..2)
+        .map(|_| {
+            let tags = TagBuilder::default()
+                .nutag_url(nua_b_url)
+                .siptag_to_str(nua_b_url)
+                .collect();
+            Handle::create(&nua_a, &tags).unwrap()
+        })
+        .collect();
+
+    {
+        let a_notifies = a_notifies.clone();
+        let a_terminated = a_terminated.clone();
+        let notifier = notifier.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingNotify = event {
+                    match sip.subscription_state().state() {
+                        SubscriptionState::Active => {
+                            a_notifies.borrow_mut().push(sip.payload().as_utf8_lossy());
+                            match a_notifies.borrow().len() {
+                                2 => {
+                                    /* both watchers are active: publish a new state */
+                                    let tags = TagBuilder::default()
+                                        .siptag_event_str("presence")
+                                        .siptag_content_type_str("text/plain")
+                                        .siptag_payload_str("closed")
+                                        .collect();
+                                    notifier.notifier(&tags);
+                                }
+                                4 => notifier.terminate(&[]),
+                                _ => {}
+                            }
+                        }
+                        SubscriptionState::Terminated => {
+                            *a_terminated.borrow_mut() += 1;
+                            if *a_terminated.borrow() == 2 {
+                                nua.root().break_();
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            },
+        );
+    }
+
+    let tags = TagBuilder::default()
+        .siptag_event_str("presence")
+        .siptag_accept_str("text/plain")
+        .siptag_expires(60)
+        .collect();
+
+    for watcher in &watchers {
+        watcher.subscribe(&tags);
+    }
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(*b_authorized.borrow(), 2);
+    assert_eq!(
+        *a_notifies.borrow(),
+        vec!["open", "open", "closed", "closed"]
+    );
+    assert_eq!(*a_terminated.borrow(), 2);
+}
@@ -0,0 +1,160 @@
+use sofia_sip::nua::Qualifier;
+use sofia_sip::nua::QualifyResult;
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_options() {
+    /*
+    A                    B
+    |-------OPTIONS----->|
+    |<--------200--------| (answered by stack B)
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let _nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_options_status = Rc::new(Cell::new(0));
+
+    {
+        let a_options_status = a_options_status.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::ReplyOptions = event {
+                    if status >= 200 {
+                        a_options_status.set(status);
+                        nua.root().break_();
+                    }
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    handle.options(&[]);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(a_options_status.get(), 200);
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_qualify_periodically() {
+    /*
+    Q (qualifier)        B
+    |-------OPTIONS----->| (on create)
+    |<--------200--------|
+    |                    |
+    |     (interval)     |
+    |                    |
+    |-------OPTIONS----->|
+    |<--------200--------|
+    |                    |
+    */
+    let nua_q = {
+        let tags = TagBuilder::default()
+            .nutag_url("sip:127.0.0.1:5080")
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let _nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let root = Root::get_default_root().unwrap();
+    let results = Rc::new(RefCell::new(Vec::<QualifyResult>::new()));
+
+    let _qualifier = {
+        let results = results.clone();
+        Qualifier::create(
+            &nua_q,
+            &[nua_b_url],
+            Duration::from_millis(500),
+            move |result: &QualifyResult| {
+                println!("[QUALIFY]{:?}", result);
+                results.borrow_mut().push(result.clone());
+                if results.borrow().len() == 2 {
+                    root.break_();
+                }
+            },
+        )
+        .unwrap()
+    };
+
+    root.run();
+
+    let results = results.borrow();
+    assert_eq!(results.len(), 2);
+    for result in results.iter() {
+        assert_eq!(result.uri(), nua_b_url);
+        assert_eq!(result.status(), 200);
+        assert!(result.reachable());
+        assert!(result.rtt() < Duration::from_secs(1));
+    }
+}
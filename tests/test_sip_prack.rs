@@ -0,0 +1,159 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_reliable_session_progress_and_prack() {
+    /*
+    A                    B
+    |-------INVITE------>| (Supported: 100rel)
+    |<----100 Trying-----|
+    |                    |
+    |<--183 (SDP, RSeq)--| (Require: 100rel)
+    |--------PRACK------>|
+    |<--------200--------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_a_url)
+            .nutag_early_media(true)
+            .nutag_autoprack(false)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_early_media(true)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_early_sdp = Rc::new(Cell::new(false));
+    let a_prack_status = Rc::new(Cell::new(0));
+    let b_received_prack = Rc::new(Cell::new(false));
+    /* B keeps the early call to answer the PRACK on it */
+    let b_call: Rc<RefCell<Option<Handle>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_received_prack = b_received_prack.clone();
+        let b_call = b_call.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let handle = handle.unwrap();
+                        handle
+                            .respond_session_progress("m=audio 5010 RTP/AVP 8", &[])
+                            .unwrap();
+                        *b_call.borrow_mut() = Some(handle.clone());
+                    }
+                    NuaEvent::IncomingPrack => {
+                        b_received_prack.set(true);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    {
+        let a_early_sdp = a_early_sdp.clone();
+        let a_prack_status = a_prack_status.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyInvite if status == 183 => {
+                        a_early_sdp.set(sip.payload().as_utf8_lossy().contains("m=audio 5010"));
+                        handle.unwrap().prack(&[]);
+                    }
+                    NuaEvent::ReplyPrack if status >= 200 => {
+                        a_prack_status.set(status);
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .siptag_supported_str("100rel")
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert!(a_early_sdp.get());
+    assert_eq!(a_prack_status.get(), 200);
+    assert!(b_received_prack.get());
+    assert!(b_call.borrow().is_some());
+}
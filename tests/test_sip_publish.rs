@@ -0,0 +1,191 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_publish_refresh_and_unpublish() {
+    /*
+    A                    B (event state compositor)
+    |-------PUBLISH----->| (with body)
+    |<--200 (SIP-ETag)---|
+    |                    |
+    |-------PUBLISH----->| (automatic refresh, SIP-If-Match)
+    |<--200 (SIP-ETag)---|
+    |                    |
+    |-------PUBLISH----->| (unpublish, SIP-If-Match, expires 0)
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |    nua::handle(B)   |                     |                     |
+    |-------------------->|                     |                     |
+    |                     |                     |                     |
+    |  handle::publish()  |                     |                     |
+    |------------------->[_]     [PUBLISH]      |                     |
+    |                    [_]------------------>[_]  IncomingPublish   |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  respond_publish() |
+    |                    [_]   [200 OK + ETag] [_]<-------------------|
+    |    ReplyPublish    [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("PUBLISH")
+            .nutag_allow_events("presence")
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* (SIP-If-Match received, SIP-ETag generated) for each PUBLISH received by B */
+    let b_publishes = Rc::new(RefCell::new(Vec::<(String, String)>::new()));
+    /* (status, SIP-ETag) of every final response received by A */
+    let a_replies = Rc::new(RefCell::new(Vec::<(NuaEvent, u32, String)>::new()));
+
+    {
+        let b_publishes = b_publishes.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingPublish = event {
+                    let if_match = sip.if_match().to_string();
+                    let expires = if b_publishes.borrow().len() == 2 {
+                        0
+                    } else {
+                        2
+                    };
+                    let etag = handle.unwrap().respond_publish(expires, &[]).unwrap();
+                    b_publishes.borrow_mut().push((if_match, etag));
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str("sip:alice@127.0.0.1")
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    {
+        let a_replies = a_replies.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyPublish if status >= 200 => {
+                        a_replies
+                            .borrow_mut()
+                            .push((event, status, sip.etag().to_string()));
+                        if a_replies.borrow().len() == 2 {
+                            /* initial publication and its refresh are done */
+                            handle.unwrap().unpublish(&[]);
+                        }
+                    }
+                    NuaEvent::ReplyUnpublish if status >= 200 => {
+                        a_replies
+                            .borrow_mut()
+                            .push((event, status, sip.etag().to_string()));
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let tags = TagBuilder::default()
+        .siptag_event_str("presence")
+        .siptag_content_type_str("text/plain")
+        .siptag_payload_str("open")
+        .siptag_expires(2)
+        .collect();
+
+    handle.publish(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    let a_replies = a_replies.borrow();
+    let b_publishes = b_publishes.borrow();
+    assert_eq!(a_replies.len(), 3);
+    assert_eq!(b_publishes.len(), 3);
+
+    assert!(matches!(a_replies[0], (NuaEvent::ReplyPublish, 200, _)));
+    assert!(matches!(a_replies[1], (NuaEvent::ReplyPublish, 200, _)));
+    assert!(matches!(a_replies[2], (NuaEvent::ReplyUnpublish, 200, _)));
+
+    /* A receives the entity tags generated by B */
+    assert_eq!(a_replies[0].2, b_publishes[0].1);
+    assert_eq!(a_replies[1].2, b_publishes[1].1);
+    /* the initial publication has no SIP-If-Match, the next ones match the last entity tag */
+    assert_eq!(b_publishes[0].0, "");
+    assert_eq!(b_publishes[1].0, b_publishes[0].1);
+    assert_eq!(b_publishes[2].0, b_publishes[1].1);
+    /* each entity tag is unique */
+    assert_ne!(b_publishes[0].1, b_publishes[1].1);
+}
@@ -0,0 +1,331 @@
+use sofia_sip::nua::EventData;
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_blind_transfer() {
+    /*
+    A                    B                    C
+    |-------INVITE------>|                    |
+    |<--------200--------|                    |
+    |---------ACK------->|                    |
+    |                    |                    |
+    |-------REFER------->| (Refer-To: C)      |
+    |<--------202--------|                    |
+    |<-NOTIFY (100 Trying)                    |
+    |                    |-------INVITE------>|
+    |                    |<--------200--------|
+    |                    |---------ACK------->|
+    |<-NOTIFY (200 OK)---|                    |
+    |                    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_c_url = "sip:127.0.0.1:5082";
+    let mut nua_c = {
+        let tags = TagBuilder::default().nutag_url(nua_c_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_refer_status = Rc::new(Cell::new(0));
+    /* status of each sipfrag received by A */
+    let a_progress = Rc::new(RefCell::new(Vec::<u32>::new()));
+    let b_refer_to = Rc::new(RefCell::new(String::new()));
+    /* calls of B, checked at the end */
+    let b_calls = Rc::new(RefCell::new(Vec::<Handle>::new()));
+
+    {
+        /* NUA B (transferee) */
+        let b_refer_to = b_refer_to.clone();
+        let b_calls = b_calls.clone();
+        nua_b.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let handle = handle.unwrap();
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        handle.respond(200, "OK", &tags).unwrap();
+                        b_calls.borrow_mut().push(handle.clone());
+                    }
+                    NuaEvent::IncomingRefer => {
+                        let refer_to = sip.refer_to().url().clone();
+                        *b_refer_to.borrow_mut() = refer_to.clone();
+                        let refer_event = tags
+                            .iter()
+                            .find(|tag| matches!(tag, Tag::NuReferEvent(_)))
+                            .unwrap()
+                            .clone();
+                        if let Tag::NuReferEvent(event) = &refer_event {
+                            assert!(event.value().starts_with("refer"));
+                        }
+
+                        let call = {
+                            let tags = TagBuilder::default()
+                                .nutag_url(&refer_to)
+                                .siptag_to_str(&refer_to)
+                                .collect();
+                            Handle::create(nua, &tags).unwrap()
+                        };
+                        let tags = TagBuilder::default()
+                            .tag(refer_event)
+                            .soatag_user_sdp_str("m=audio 5012 RTP/AVP 8")
+                            .collect();
+                        call.invite_referred(handle.unwrap(), &tags);
+                        b_calls.borrow_mut().push(call);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    nua_c.callback(
+        |_nua: &mut Nua,
+         event: NuaEvent,
+         status: u32,
+         phrase: String,
+         handle: Option<&Handle>,
+         _sip: Sip,
+         _tags: Vec<Tag>| {
+            /* NUA C (transfer target) */
+            println!(
+                "[NUA __C]Event: {:?} // status: {:?} // phrase: {:?}",
+                &event, &status, &phrase
+            );
+            if let NuaEvent::IncomingInvite = event {
+                let tags = TagBuilder::default()
+                    .soatag_user_sdp_str("m=audio 5014 RTP/AVP 8")
+                    .collect();
+                handle.unwrap().respond(200, "OK", &tags).unwrap();
+            }
+        },
+    );
+
+    {
+        /* NUA A (transferor) */
+        let a_refer_status = a_refer_status.clone();
+        let a_progress = a_progress.clone();
+        let carol = "<sip:carol@127.0.0.1:5082>";
+        nua_a.event_callback(move |nua: &mut Nua, data: EventData| {
+            println!("[NUA A_]EventData: {:?}", &data);
+            match data {
+                EventData::IncomingActive { handle } => {
+                    let tags = TagBuilder::default().siptag_refer_to_str(carol).collect();
+                    handle.refer(&tags);
+                }
+                EventData::Raw(raw)
+                    if raw.event == NuaEvent::ReplyRefer
+                        && a_refer_status.get() == 0
+                        && raw.status >= 200 =>
+                {
+                    a_refer_status.set(raw.status);
+                }
+                EventData::ReferProgress { status, .. } => {
+                    a_progress.borrow_mut().push(status);
+                    if status >= 200 {
+                        nua.root().break_();
+                    }
+                }
+                _ => {}
+            }
+        });
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(a_refer_status.get(), 202);
+    assert_eq!(*b_refer_to.borrow(), "sip:carol@127.0.0.1:5082");
+    assert_eq!(b_calls.borrow().len(), 2);
+    assert_eq!(a_progress.borrow().last(), Some(&200));
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_invite_with_replaces() {
+    /*
+    A                    B
+    |---INVITE (call 1)->|
+    |<--------200--------|
+    |---------ACK------->|
+    |                    |
+    |---INVITE (call 2)->| (Replaces: call 1)
+    |                    | (B finds call 1 by the Replaces header)
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default().nutag_url(nua_b_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let b_calls = Rc::new(RefCell::new(Vec::<Handle>::new()));
+    let b_replaced = Rc::new(Cell::new(false));
+    /* A keeps the second call after the callback returns */
+    let a_call_2: Rc<RefCell<Option<Handle>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_calls = b_calls.clone();
+        let b_replaced = b_replaced.clone();
+        nua_b.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingInvite = event {
+                    let handle = handle.unwrap();
+                    let replaces = sip.replaces().to_string();
+                    if replaces.is_empty() {
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        handle.respond(200, "OK", &tags).unwrap();
+                        b_calls.borrow_mut().push(handle.clone());
+                    } else {
+                        let replaced = Handle::by_replaces(nua, &replaces).unwrap();
+                        b_replaced.set(replaced.as_ref() == b_calls.borrow().first());
+                        nua.root().break_();
+                    }
+                }
+            },
+        );
+    }
+
+    {
+        let a_call_2 = a_call_2.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingActive = event {
+                    if a_call_2.borrow().is_some() {
+                        return;
+                    }
+                    let replaces = handle.unwrap().make_replaces(false).unwrap();
+                    let call = {
+                        let tags = TagBuilder::default()
+                            .nutag_url(nua_b_url)
+                            .siptag_to_str(nua_b_url)
+                            .collect();
+                        Handle::create(nua, &tags).unwrap()
+                    };
+                    let tags = TagBuilder::default()
+                        .siptag_replaces_str(&replaces)
+                        .soatag_user_sdp_str("m=audio 5012 RTP/AVP 8")
+                        .collect();
+                    call.invite(&tags);
+                    *a_call_2.borrow_mut() = Some(call);
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert!(a_call_2.borrow().is_some());
+    assert!(b_replaced.get());
+}
@@ -0,0 +1,181 @@
+use sofia_sip::su;
+use sofia_sip::tag::Contact;
+use sofia_sip::tag::OutboundOption;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_register_refresh_and_unregister() {
+    /*
+    A                    B (registrar)
+    |------REGISTER----->|
+    |<--------200--------|
+    |                    |
+    |------REGISTER----->| (automatic refresh)
+    |<--------200--------|
+    |                    |
+    |------REGISTER----->| (unregister, expires 0)
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |    nua::handle(B)   |                     |                     |
+    |-------------------->|                     |                     |
+    |                     |                     |                     |
+    |  handle::register() |                     |                     |
+    |------------------->[_]     [REGISTER]     |                     |
+    |                    [_]------------------>[_]  IncomingRegister  |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |    ReplyRegister   [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        /* act as a registrar */
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("REGISTER")
+            .nutag_registrar(nua_b_url)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* expires of each REGISTER received by B */
+    let b_registers = Rc::new(RefCell::new(Vec::<String>::new()));
+    /* every final response received by A */
+    let a_replies = Rc::new(RefCell::new(Vec::<(NuaEvent, u32)>::new()));
+
+    {
+        let b_registers = b_registers.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                if let NuaEvent::IncomingRegister = event {
+                    let unregister = b_registers.borrow().len() == 2;
+                    let expires = if unregister { 0 } else { 2 };
+                    b_registers.borrow_mut().push(expires.to_string());
+                    let tags = TagBuilder::default()
+                        .siptag_contact(&Contact::new("sip:alice@127.0.0.1:5080").expires(expires))
+                        .siptag_expires(expires)
+                        .collect();
+                    handle.unwrap().respond(200, "OK", &tags).unwrap();
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .siptag_to_str("sip:alice@127.0.0.1")
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    {
+        let a_replies = a_replies.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::ReplyRegister if status >= 200 => {
+                        a_replies.borrow_mut().push((event, status));
+                        if a_replies.borrow().len() == 2 {
+                            /* initial registration and its refresh are done */
+                            handle.unwrap().unregister(&[]);
+                        }
+                    }
+                    NuaEvent::ReplyUnregister if status >= 200 => {
+                        a_replies.borrow_mut().push((event, status));
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let tags = TagBuilder::default()
+        .nutag_registrar(nua_b_url)
+        .nutag_m_username("alice")
+        .nutag_m_display("Alice")
+        .nutag_outbound_options(&[
+            OutboundOption::OptionsKeepalive(false),
+            OutboundOption::Validate(false),
+        ])
+        .siptag_expires(2)
+        .collect();
+
+    handle.register(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    let a_replies = a_replies.borrow();
+    assert_eq!(a_replies.len(), 3);
+    assert!(matches!(a_replies[0], (NuaEvent::ReplyRegister, 200)));
+    assert!(matches!(a_replies[1], (NuaEvent::ReplyRegister, 200)));
+    assert!(matches!(a_replies[2], (NuaEvent::ReplyUnregister, 200)));
+    assert_eq!(*b_registers.borrow(), vec!["2", "2", "0"]);
+}
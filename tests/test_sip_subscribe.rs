@@ -0,0 +1,172 @@
+use sofia_sip::su;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use sofia_sip::sip::SubscriptionState;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_subscribe_notify_and_unsubscribe() {
+    /*
+    A (watcher)          B (notifier)
+    |------SUBSCRIBE---->|
+    |<--------200--------|
+    |<-------NOTIFY------| (sent by stack B)
+    |---------200------->|
+    |                    |
+    |------SUBSCRIBE---->| (unsubscribe, expires 0)
+    |<--------200--------|
+    |                    |
+
+                           ______(NETWORK)_____
+                          /                    \
+    A                 NUA STACK (A)         NUA STACK (B)             B
+    |                     |                     |                     |
+    |    nua::handle(B)   |                     |                     |
+    |-------------------->|                     |                     |
+    |                     |                     |                     |
+    | handle::subscribe() |                     |                     |
+    |------------------->[_]    [SUBSCRIBE]     |                     |
+    |                    [_]------------------>[_] IncomingSubscribe  |
+    |                    [_]                   [_]------------------->|
+    |                    [_]                   [_]  handle::respond() |
+    |                    [_]      [200 OK]     [_]<-------------------|
+    |   ReplySubscribe   [_]<------------------[_]                    |
+    |<------------------ [_]      [NOTIFY]     [_]                    |
+    |    IncomingNotify  [_]<------------------[_]                    |
+    |<------------------ [_]                    |                     |
+    |                     |                     |                     |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default().nutag_url(nua_a_url).collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_allow("SUBSCRIBE")
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    /* (event, subscription state) of each NOTIFY received by A */
+    let a_notifies = Rc::new(RefCell::new(Vec::<(String, SubscriptionState)>::new()));
+    let a_unsubscribed = Rc::new(RefCell::new(false));
+
+    nua_b.callback(
+        |_nua: &mut Nua,
+         event: NuaEvent,
+         status: u32,
+         phrase: String,
+         handle: Option<&Handle>,
+         sip: Sip,
+         _tags: Vec<Tag>| {
+            println!(
+                "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                &event, &status, &phrase
+            );
+            if let NuaEvent::IncomingSubscribe = event {
+                assert_eq!(sip.event().r#type(), "presence");
+                if status < 200 {
+                    let tags = TagBuilder::default().siptag_expires(60).collect();
+                    handle.unwrap().respond(200, "OK", &tags).unwrap();
+                }
+            }
+        },
+    );
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    {
+        let a_notifies = a_notifies.clone();
+        let a_unsubscribed = a_unsubscribed.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingNotify => {
+                        let state = sip.subscription_state().state().clone();
+                        println!("[NUA A_]Subscription-State: {}", sip.subscription_state());
+                        a_notifies
+                            .borrow_mut()
+                            .push((sip.event().r#type().clone(), state));
+                        if a_notifies.borrow().len() == 1 {
+                            handle.unwrap().unsubscribe(&[]);
+                        }
+                    }
+                    NuaEvent::ReplyUnsubscribe if status >= 200 => {
+                        *a_unsubscribed.borrow_mut() = true;
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let tags = TagBuilder::default()
+        .siptag_event_str("presence")
+        .siptag_accept_str("application/pidf+xml")
+        .siptag_expires(60)
+        .collect();
+
+    handle.subscribe(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert!(*a_unsubscribed.borrow());
+    let a_notifies = a_notifies.borrow();
+    assert!(a_notifies.len() >= 1);
+    assert_eq!(a_notifies[0].0, "presence");
+    assert_ne!(a_notifies[0].1, SubscriptionState::Terminated);
+}
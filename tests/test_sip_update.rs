@@ -0,0 +1,176 @@
+use sofia_sip::su;
+use sofia_sip::tag::SessionRefresher;
+use sofia_sip::Handle;
+use sofia_sip::Nua;
+use sofia_sip::NuaEvent;
+use sofia_sip::Root;
+use sofia_sip::Sip;
+use sofia_sip::Tag;
+use sofia_sip::TagBuilder;
+
+use adorn::adorn;
+use serial_test::serial;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+fn wrap(f: fn()) {
+    /* manual deinit (because tests do not run atexit) */
+    if let Err(e) = std::panic::catch_unwind(|| {
+        su::init().unwrap();
+        su::init_default_root().unwrap();
+        f();
+        su::deinit_default_root();
+        su::deinit();
+    }) {
+        su::deinit_default_root();
+        su::deinit();
+        println!(
+            "******************************************************\n\
+             PANIC INSIDE WRAPPER\n\
+             `#[adorn(wrap)]` may give a wrong line that panicked\n\
+             ******************************************************\n"
+        );
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[test]
+#[adorn(wrap)]
+#[serial]
+fn test_case_session_refresh_with_update() {
+    /*
+    A (refresher)        B
+    |-------INVITE------>| (Session-Expires: 2;refresher=uac)
+    |<--------200--------|
+    |---------ACK------->|
+    |                    |
+    |       (wait for half of the session interval)
+    |                    |
+    |-------UPDATE------>| (session refresh)
+    |<--------200--------|
+    |                    |
+    */
+    let nua_a_url = "sip:127.0.0.1:5080";
+    let mut nua_a = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_a_url)
+            .nutag_session_timer(2)
+            .nutag_min_se(1)
+            .nutag_session_refresher(SessionRefresher::Local)
+            .nutag_update_refresh(true)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+    let nua_b_url = "sip:127.0.0.1:5081";
+    let mut nua_b = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .nutag_min_se(1)
+            .collect();
+        Nua::create(&tags).unwrap()
+    };
+
+    let a_update_status = Rc::new(Cell::new(0));
+    /* time between the call setup and the refresh */
+    let a_active_at: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+    let a_refresh_after: Rc<Cell<Option<Duration>>> = Rc::new(Cell::new(None));
+    let b_received_update = Rc::new(Cell::new(false));
+    let b_terminated = Rc::new(Cell::new(false));
+    /* B keeps the incoming call to check it survives the refresh */
+    let b_call: Rc<RefCell<Option<Handle>>> = Rc::new(RefCell::new(None));
+
+    {
+        let b_received_update = b_received_update.clone();
+        let b_terminated = b_terminated.clone();
+        let b_call = b_call.clone();
+        nua_b.callback(
+            move |_nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA _B]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingInvite => {
+                        let tags = TagBuilder::default()
+                            .soatag_user_sdp_str("m=audio 5010 RTP/AVP 8")
+                            .collect();
+                        let handle = handle.unwrap();
+                        handle.respond(200, "OK", &tags).unwrap();
+                        *b_call.borrow_mut() = Some(handle.clone());
+                    }
+                    NuaEvent::IncomingUpdate => {
+                        b_received_update.set(true);
+                    }
+                    NuaEvent::IncomingBye | NuaEvent::IncomingTerminated => {
+                        b_terminated.set(true);
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    {
+        let a_update_status = a_update_status.clone();
+        let a_active_at = a_active_at.clone();
+        let a_refresh_after = a_refresh_after.clone();
+        nua_a.callback(
+            move |nua: &mut Nua,
+                  event: NuaEvent,
+                  status: u32,
+                  phrase: String,
+                  _handle: Option<&Handle>,
+                  _sip: Sip,
+                  _tags: Vec<Tag>| {
+                println!(
+                    "[NUA A_]Event: {:?} // status: {:?} // phrase: {:?}",
+                    &event, &status, &phrase
+                );
+                match event {
+                    NuaEvent::IncomingActive => a_active_at.set(Some(Instant::now())),
+                    NuaEvent::ReplyUpdate if status >= 200 => {
+                        a_update_status.set(status);
+                        a_refresh_after.set(a_active_at.get().map(|at| at.elapsed()));
+                        nua.root().break_();
+                    }
+                    _ => {}
+                }
+            },
+        );
+    }
+
+    let handle = {
+        let tags = TagBuilder::default()
+            .nutag_url(nua_b_url)
+            .siptag_to_str(nua_b_url)
+            .collect();
+        Handle::create(&nua_a, &tags).unwrap()
+    };
+
+    let tags = TagBuilder::default()
+        .soatag_user_sdp_str("m=audio 5008 RTP/AVP 8")
+        .collect();
+
+    handle.invite(&tags);
+
+    Root::get_default_root().unwrap().run();
+
+    assert_eq!(a_update_status.get(), 200);
+    assert!(b_received_update.get());
+    /* sent by the session timer (half of the 2s interval), not at call setup */
+    assert!(a_refresh_after.get().unwrap() >= Duration::from_millis(500));
+    /* the session was refreshed, not torn down */
+    assert!(!b_terminated.get());
+    assert!(b_call.borrow().as_ref().unwrap().has_active_call());
+    assert!(handle.has_active_call());
+}
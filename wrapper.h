@@ -1,4 +1,9 @@
 #include <sofia-sip/nua.h>
+#include <sofia-sip/nea.h>
+#include <sofia-sip/msg.h>
+#include <sofia-sip/sip.h>
+#include <sofia-sip/sip_util.h>
+#include <sofia-sip/msg_mclass.h>
 
 #include <stdlib.h>
 